@@ -0,0 +1,38 @@
+use crate::scaler::resize_to;
+use image::{imageops::FilterType, DynamicImage, GrayImage, ImageBuffer, Luma, Rgb};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlphaOptions {
+    pub background: Option<Rgb<u8>>,
+    pub threshold: Option<u8>,
+}
+
+pub(crate) fn composite(image: &DynamicImage, background: Rgb<u8>) -> DynamicImage {
+    let rgba = image.to_rgba8();
+
+    DynamicImage::ImageRgb8(ImageBuffer::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let a = a as u32;
+
+        let color = [r, g, b];
+
+        Rgb([0, 1, 2]
+            .map(|i| ((color[i] as u32 * a + background.0[i] as u32 * (255 - a)) / 255) as u8))
+    }))
+}
+
+pub(crate) fn empty_cells(
+    image: &DynamicImage,
+    dimensions: (u32, u32),
+    threshold: u8,
+) -> Vec<bool> {
+    let rgba = image.to_rgba8();
+    let alpha: GrayImage = ImageBuffer::from_fn(rgba.width(), rgba.height(), |x, y| {
+        Luma([rgba.get_pixel(x, y).0[3]])
+    });
+
+    resize_to(&alpha, dimensions, FilterType::Triangle)
+        .pixels()
+        .map(|alpha| alpha.0[0] < threshold)
+        .collect()
+}
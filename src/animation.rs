@@ -0,0 +1,84 @@
+use crate::Result;
+use image::{
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        png::PngDecoder,
+    },
+    AnimationDecoder, Delay, DynamicImage, Frame, Frames, ImageFormat,
+};
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub fn open_frames(path: &Path) -> Result<Vec<(DynamicImage, Duration)>> {
+    if path.is_dir() {
+        return open_directory(path);
+    }
+
+    load_frames(&fs::read(path)?)
+}
+
+pub fn load_frames(data: &[u8]) -> Result<Vec<(DynamicImage, Duration)>> {
+    let frames = match image::guess_format(data).ok() {
+        Some(ImageFormat::Gif) => GifDecoder::new(Cursor::new(data))?.into_frames(),
+
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(Cursor::new(data))?;
+
+            if !decoder.is_apng() {
+                return Ok(vec![(image::load_from_memory(data)?, Duration::default())]);
+            }
+
+            decoder.apng().into_frames()
+        }
+
+        _ => return Ok(vec![(image::load_from_memory(data)?, Duration::default())]),
+    };
+
+    collect(frames)
+}
+
+fn open_directory(path: &Path) -> Result<Vec<(DynamicImage, Duration)>> {
+    let mut paths = fs::read_dir(path)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?;
+
+    paths.retain(|path| path.is_file() && ImageFormat::from_path(path).is_ok());
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| Ok((image::open(path)?, Duration::default())))
+        .collect()
+}
+
+fn collect(frames: Frames<'_>) -> Result<Vec<(DynamicImage, Duration)>> {
+    Ok(frames
+        .collect_frames()?
+        .into_iter()
+        .map(|frame| {
+            let delay = Duration::from(frame.delay());
+
+            (DynamicImage::ImageRgba8(frame.into_buffer()), delay)
+        })
+        .collect())
+}
+
+pub fn write_gif<W: Write>(writer: W, frames: &[(DynamicImage, Duration)]) -> Result<()> {
+    let mut encoder = GifEncoder::new(writer);
+
+    encoder.set_repeat(Repeat::Infinite)?;
+    encoder.encode_frames(frames.iter().map(|(image, delay)| {
+        Frame::from_parts(
+            image.to_rgba8(),
+            0,
+            0,
+            Delay::from_numer_denom_ms(delay.as_millis() as u32, 1),
+        )
+    }))?;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use crate::{Error, Result};
+use image::Rgb;
+
+#[derive(Clone, Copy, Debug)]
+pub enum AnsiMode {
+    TrueColor,
+    Xterm256,
+    Basic16,
+}
+
+const BASIC_16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+impl AnsiMode {
+    pub fn parse(mode: &str) -> Result<AnsiMode> {
+        match mode {
+            "truecolor" => Ok(AnsiMode::TrueColor),
+            "256" => Ok(AnsiMode::Xterm256),
+            "16" => Ok(AnsiMode::Basic16),
+            _ => Err(Error::UnsupportedAnsiMode(mode.into())),
+        }
+    }
+
+    pub fn sgr(self, color: Rgb<u8>, background: bool) -> String {
+        let [r, g, b] = color.0;
+
+        match self {
+            AnsiMode::TrueColor => {
+                format!("{};2;{};{};{}", if background { 48 } else { 38 }, r, g, b)
+            }
+
+            AnsiMode::Xterm256 => {
+                let cube = |v: u8| match v {
+                    0..=47 => 0,
+                    48..=114 => 1,
+                    _ => (v as u32 - 35) / 40,
+                };
+                let level = |i: u32| if i == 0 { 0 } else { 55 + i * 40 };
+
+                let (cr, cg, cb) = (cube(r), cube(g), cube(b));
+                let cube_index = 16 + 36 * cr + 6 * cg + cb;
+                let cube_color = [level(cr), level(cg), level(cb)];
+
+                let average = (r as u32 + g as u32 + b as u32) / 3;
+                let gray = if average > 238 {
+                    23
+                } else {
+                    average.saturating_sub(3) / 10
+                };
+                let gray_index = 232 + gray;
+                let gray_level = 8 + gray * 10;
+
+                let index = if distance(color.0, cube_color) <= distance(color.0, [gray_level; 3]) {
+                    cube_index
+                } else {
+                    gray_index
+                };
+
+                format!("{};5;{}", if background { 48 } else { 38 }, index)
+            }
+
+            AnsiMode::Basic16 => {
+                let index = (0..16)
+                    .min_by_key(|i| distance(color.0, BASIC_16[*i].map(|c| c as u32)))
+                    .unwrap();
+
+                let base = if background { 40 } else { 30 };
+                let base = if index < 8 { base } else { base + 60 };
+
+                format!("{}", base + index % 8)
+            }
+        }
+    }
+}
+
+fn distance(a: [u8; 3], b: [u32; 3]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truecolor_passes_colors_through() {
+        assert_eq!(AnsiMode::TrueColor.sgr(Rgb([1, 2, 3]), false), "38;2;1;2;3");
+        assert_eq!(AnsiMode::TrueColor.sgr(Rgb([1, 2, 3]), true), "48;2;1;2;3");
+    }
+
+    #[test]
+    fn xterm256_picks_cube_or_gray() {
+        let sgr = |color: [u8; 3]| AnsiMode::Xterm256.sgr(Rgb(color), false);
+
+        assert_eq!(sgr([0, 0, 0]), "38;5;16");
+        assert_eq!(sgr([255, 255, 255]), "38;5;231");
+        assert_eq!(sgr([255, 0, 0]), "38;5;196");
+        assert_eq!(sgr([95, 135, 175]), "38;5;67");
+        assert_eq!(sgr([128, 128, 128]), "38;5;244");
+        assert_eq!(sgr([238, 238, 238]), "38;5;255");
+        assert_eq!(AnsiMode::Xterm256.sgr(Rgb([255, 0, 0]), true), "48;5;196");
+    }
+
+    #[test]
+    fn basic16_picks_the_nearest_palette_entry() {
+        let sgr = |color: [u8; 3], background: bool| AnsiMode::Basic16.sgr(Rgb(color), background);
+
+        assert_eq!(sgr([0, 0, 0], false), "30");
+        assert_eq!(sgr([205, 0, 0], true), "41");
+        assert_eq!(sgr([255, 0, 0], false), "91");
+        assert_eq!(sgr([255, 255, 255], true), "107");
+        assert_eq!(sgr([120, 120, 130], false), "90");
+    }
+}
@@ -0,0 +1,458 @@
+use crate::{
+    raster::{raster_cache, text_layout, RasterOptions},
+    AnsiMode, AsciiImageBuilder, Error, Result,
+};
+use fontdue::Font;
+use image::{
+    DynamicImage, GenericImage, GrayImage, ImageBuffer, Luma, Pixel, Rgb, RgbImage, Rgba, RgbaImage,
+};
+use std::fmt::{self, Display};
+
+#[derive(Clone, Debug)]
+pub struct HtmlOptions {
+    pub background: bool,
+    pub font_family: String,
+    pub line_height: f32,
+    pub fragment: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            background: false,
+            font_family: "Consolas, monospace".into(),
+            line_height: 1.,
+            fragment: false,
+        }
+    }
+}
+
+pub struct AsciiImage {
+    width: u32,
+    height: u32,
+    chars: Vec<char>,
+    fg: Vec<Rgb<u8>>,
+    bg: Option<Vec<Rgb<u8>>>,
+    empty: Option<Vec<bool>>,
+}
+
+impl AsciiImage {
+    pub fn new(luma: GrayImage, colors: RgbImage, table: Vec<char>) -> Result<AsciiImage> {
+        let fg = average_colors(&colors, luma.dimensions());
+
+        AsciiImage::from_luma(luma, fg, table)
+    }
+
+    pub(crate) fn from_luma(
+        luma: GrayImage,
+        fg: Vec<Rgb<u8>>,
+        table: Vec<char>,
+    ) -> Result<AsciiImage> {
+        if table.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+
+        let chars = luma
+            .pixels()
+            .map(|luma| {
+                table[(luma.0[0] as f64 / 255. * (table.len() - 1) as f64).trunc() as usize]
+            })
+            .collect();
+
+        let (width, height) = luma.dimensions();
+
+        Ok(AsciiImage {
+            width,
+            height,
+            chars,
+            fg,
+            bg: None,
+            empty: None,
+        })
+    }
+
+    pub(crate) fn from_chars(
+        width: u32,
+        height: u32,
+        chars: Vec<char>,
+        fg: Vec<Rgb<u8>>,
+        bg: Option<Vec<Rgb<u8>>>,
+    ) -> AsciiImage {
+        AsciiImage {
+            width,
+            height,
+            chars,
+            fg,
+            bg,
+            empty: None,
+        }
+    }
+
+    pub(crate) fn chars_mut(&mut self) -> &mut [char] {
+        &mut self.chars
+    }
+
+    pub(crate) fn clear_cells(&mut self, empty: Vec<bool>) {
+        for (c, empty) in self.chars.iter_mut().zip(&empty) {
+            if *empty {
+                *c = ' ';
+            }
+        }
+
+        self.empty = Some(empty);
+    }
+
+    pub fn is_empty_at(&self, x: u32, y: u32) -> bool {
+        let index = (y * self.width + x) as usize;
+
+        self.empty.as_ref().is_some_and(|empty| empty[index])
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn builder() -> AsciiImageBuilder {
+        AsciiImageBuilder::default()
+    }
+
+    pub fn char_at(&self, x: u32, y: u32) -> char {
+        self.chars[(y * self.width + x) as usize]
+    }
+
+    fn colors_at(&self, x: u32, y: u32) -> (Rgb<u8>, Option<Rgb<u8>>) {
+        let index = (y * self.width + x) as usize;
+
+        let paper = self.bg.as_ref().filter(|_| !self.is_empty_at(x, y));
+
+        (self.fg[index], paper.map(|bg| bg[index]))
+    }
+
+    pub fn rasterize(&self, font: &Font, options: &RasterOptions) -> DynamicImage {
+        let (cache, (cell_width, cell_height)) =
+            raster_cache(font, self.chars.iter().copied(), options);
+
+        let mut img: RgbaImage =
+            ImageBuffer::new(self.width * cell_width, self.height * cell_height);
+
+        for iy in 0..self.height {
+            for ix in 0..self.width {
+                if self.is_empty_at(ix, iy) {
+                    continue;
+                }
+
+                let (color, paper) = self.colors_at(ix, iy);
+                let (color, paper) = if options.rgb {
+                    (color, paper.unwrap_or(options.background))
+                } else {
+                    (options.foreground, options.background)
+                };
+
+                let mut sub_img =
+                    img.sub_image(ix * cell_width, iy * cell_height, cell_width, cell_height);
+                let raster = &cache[&self.char_at(ix, iy)];
+
+                for sy in 0..cell_height {
+                    for sx in 0..cell_width {
+                        let ink = (255 - raster.get_pixel(sx, sy).0[0]) as u32;
+
+                        let pixel = if options.transparent {
+                            let [r, g, b] = color.0;
+
+                            Rgba([r, g, b, ink as u8])
+                        } else {
+                            let [r, g, b] = [0, 1, 2].map(|i| {
+                                ((color.0[i] as u32 * ink + paper.0[i] as u32 * (255 - ink)) / 255)
+                                    as u8
+                            });
+
+                            Rgba([r, g, b, 255])
+                        };
+
+                        sub_img.put_pixel(sx, sy, pixel);
+                    }
+                }
+            }
+        }
+
+        let gray = |color: Rgb<u8>| color.0.iter().all(|c| *c == color.0[0]);
+
+        if options.transparent || self.empty.iter().flatten().any(|empty| *empty) {
+            DynamicImage::ImageRgba8(img)
+        } else if !options.rgb && gray(options.foreground) && gray(options.background) {
+            DynamicImage::ImageLuma8(ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+                Luma([img.get_pixel(x, y).0[0]])
+            }))
+        } else {
+            DynamicImage::ImageRgb8(ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+                let [r, g, b, _] = img.get_pixel(x, y).0;
+
+                Rgb([r, g, b])
+            }))
+        }
+    }
+
+    pub fn to_svg(
+        &self,
+        font: &Font,
+        options: &RasterOptions,
+        family: Option<&str>,
+        font_data: Option<&[u8]>,
+    ) -> String {
+        let ((cell_width, cell_height), (left, baseline)) =
+            text_layout(font, self.chars.iter().copied(), options);
+        let (width, height) = (self.width * cell_width, self.height * cell_height);
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        );
+
+        if let (Some(family), Some(data)) = (family, font_data) {
+            svg += &format!(
+                r#"<style>@font-face{{font-family:"{}";src:url(data:font/ttf;base64,{})}}</style>"#,
+                family,
+                base64::encode(data)
+            );
+        }
+
+        if !options.transparent {
+            svg += &format!(
+                r#"<rect width="100%" height="100%" fill="{}"/>"#,
+                hex(options.background)
+            );
+        }
+
+        if options.rgb && self.bg.is_some() {
+            for iy in 0..self.height {
+                let mut ix = 0;
+
+                while ix < self.width {
+                    let paper = self.colors_at(ix, iy).1;
+                    let start = ix;
+
+                    while ix < self.width && self.colors_at(ix, iy).1 == paper {
+                        ix += 1;
+                    }
+
+                    if let Some(paper) = paper {
+                        svg += &format!(
+                            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                            start * cell_width,
+                            iy * cell_height,
+                            (ix - start) * cell_width,
+                            cell_height,
+                            hex(paper)
+                        );
+                    }
+                }
+            }
+        }
+
+        svg += &format!(
+            r#"<g font-family="{}monospace" font-size="{}" fill="{}" xml:space="preserve">"#,
+            family
+                .map(|family| format!("{}, ", family))
+                .unwrap_or_default(),
+            options.font_size,
+            hex(options.foreground)
+        );
+
+        for iy in 0..self.height {
+            svg += &format!(
+                r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacing">"#,
+                left,
+                iy as i32 * cell_height as i32 + baseline,
+                width
+            );
+
+            if options.rgb {
+                let mut ix = 0;
+
+                while ix < self.width {
+                    let color = self.colors_at(ix, iy).0;
+                    let mut run = String::new();
+
+                    while ix < self.width && self.colors_at(ix, iy).0 == color {
+                        run.push(self.char_at(ix, iy));
+                        ix += 1;
+                    }
+
+                    svg += &format!(r#"<tspan fill="{}">{}</tspan>"#, hex(color), escape(&run));
+                }
+            } else {
+                let line = (0..self.width)
+                    .map(|ix| self.char_at(ix, iy))
+                    .collect::<String>();
+
+                svg += &escape(&line);
+            }
+
+            svg += "</text>";
+        }
+
+        svg + "</g></svg>\n"
+    }
+
+    pub fn to_ansi(&self, mode: AnsiMode, background: bool) -> String {
+        (0..self.height)
+            .map(|iy| {
+                let mut line = String::new();
+                let mut last = None;
+
+                for ix in 0..self.width {
+                    let (color, paper) = self.colors_at(ix, iy);
+
+                    let sgr = if self.is_empty_at(ix, iy) {
+                        String::from("0")
+                    } else if let Some(paper) = paper {
+                        format!("{};{}", mode.sgr(color, false), mode.sgr(paper, true))
+                    } else if background {
+                        format!(
+                            "{};{}",
+                            mode.sgr(contrast_ink(color), false),
+                            mode.sgr(color, true)
+                        )
+                    } else {
+                        mode.sgr(color, false)
+                    };
+
+                    if last.as_ref() != Some(&sgr) {
+                        line += &format!("\x1b[{}m", sgr);
+                        last = Some(sgr);
+                    }
+
+                    line.push(self.char_at(ix, iy));
+                }
+
+                line + "\x1b[0m"
+            })
+            .collect::<Vec<String>>()
+            .join("\r\n")
+    }
+
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let lines = (0..self.height)
+            .map(|iy| {
+                let mut line = String::new();
+                let mut ix = 0;
+
+                while ix < self.width {
+                    let style = self.html_style(ix, iy, options.background);
+                    let mut run = String::new();
+
+                    while ix < self.width && self.html_style(ix, iy, options.background) == style {
+                        run.push(self.char_at(ix, iy));
+                        ix += 1;
+                    }
+
+                    if style.is_empty() {
+                        line += &escape(&run);
+                    } else {
+                        line += &format!(r#"<span style="{}">{}</span>"#, style, escape(&run));
+                    }
+                }
+
+                line
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let pre = format!(
+            r#"<pre style="font-family:{};line-height:{}">{}</pre>"#,
+            escape(&options.font_family).replace('"', "&quot;"),
+            options.line_height,
+            lines
+        );
+
+        if options.fragment {
+            pre + "\n"
+        } else {
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body>\n{}\n</body>\n</html>\n",
+                pre
+            )
+        }
+    }
+
+    fn html_style(&self, x: u32, y: u32, background: bool) -> String {
+        let (color, paper) = self.colors_at(x, y);
+
+        if self.is_empty_at(x, y) {
+            String::new()
+        } else if let Some(paper) = paper {
+            format!("color:{};background-color:{}", hex(color), hex(paper))
+        } else if background {
+            format!(
+                "color:{};background-color:{}",
+                hex(contrast_ink(color)),
+                hex(color)
+            )
+        } else {
+            format!("color:{}", hex(color))
+        }
+    }
+}
+
+fn contrast_ink(paper: Rgb<u8>) -> Rgb<u8> {
+    if paper.to_luma().0[0] < 128 {
+        Rgb([255; 3])
+    } else {
+        Rgb([0; 3])
+    }
+}
+
+fn hex(color: Rgb<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0[0], color.0[1], color.0[2])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub(crate) fn average_colors(colors: &RgbImage, (width, height): (u32, u32)) -> Vec<Rgb<u8>> {
+    (0..height)
+        .flat_map(|iy| (0..width).map(move |ix| (ix, iy)))
+        .map(|(ix, iy)| average_color(colors, (width, height), ix, iy))
+        .collect()
+}
+
+fn average_color(colors: &RgbImage, (width, height): (u32, u32), ix: u32, iy: u32) -> Rgb<u8> {
+    let x0 = ix * colors.width() / width;
+    let y0 = iy * colors.height() / height;
+    let x1 = ((ix + 1) * colors.width() / width).max(x0 + 1);
+    let y1 = ((iy + 1) * colors.height() / height).max(y0 + 1);
+
+    let mut sum = [0u64; 3];
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let pixel = colors.get_pixel(x, y);
+
+            for (s, p) in sum.iter_mut().zip(pixel.0.iter()) {
+                *s += *p as u64;
+            }
+        }
+    }
+
+    let count = ((x1 - x0) * (y1 - y0)) as u64;
+
+    Rgb(sum.map(|s| (s / count) as u8))
+}
+
+impl Display for AsciiImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = (0..self.height)
+            .map(|iy| {
+                (0..self.width)
+                    .map(|ix| self.char_at(ix, iy))
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\r\n");
+
+        write!(f, "{}", text)
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{
+    alpha::{composite, empty_cells},
+    cast::cast,
+    cell_aspect, default_font_data, generate_table, load_font,
+    luminance::Source,
+    shared_default_font, AlphaOptions, AnsiMode, AsciiImage, Dither, EdgeOptions, Error, GlyphFit,
+    HtmlOptions, Luminance, Matching, RasterOptions, Renderer, Result, Scaler, Theme, Tone,
+    SUB_CELL_HEIGHT, SUB_CELL_WIDTH,
+};
+use fontdue::Font;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, Pixel, Rgb};
+use std::time::Duration;
+
+const DEFAULT_TABLE: &str = "@%#*+=-:. ";
+const DEFAULT_FONT_FAMILY: &str = "Consolas";
+const EMBEDDED_FONT_FAMILY: &str = "ascii-image";
+
+#[derive(Clone, Copy, Debug)]
+pub enum OutputMode {
+    Text,
+    Ansi { mode: AnsiMode, background: bool },
+    Raster,
+    Svg { embed_font: bool },
+    Html,
+}
+
+pub enum Output {
+    Text(String),
+    Image(DynamicImage),
+}
+
+pub struct AsciiImageBuilder {
+    table: Option<Vec<char>>,
+    auto_table: Option<(Vec<char>, usize)>,
+    scaler: Scaler,
+    cell_aspect: Option<f64>,
+    filter: FilterType,
+    dither: Dither,
+    luminance: Luminance,
+    linear: bool,
+    tone: Tone,
+    edges: Option<EdgeOptions>,
+    alpha: AlphaOptions,
+    matching: Matching,
+    renderer: Renderer,
+    font: Option<(Font, Vec<u8>)>,
+    raster: RasterOptions,
+    html: HtmlOptions,
+    output: OutputMode,
+}
+
+impl Default for AsciiImageBuilder {
+    fn default() -> Self {
+        AsciiImageBuilder {
+            table: None,
+            auto_table: None,
+            scaler: Scaler::default(),
+            cell_aspect: None,
+            filter: FilterType::Lanczos3,
+            dither: Dither::None,
+            luminance: Luminance::Rec709,
+            linear: false,
+            tone: Tone::default(),
+            edges: None,
+            alpha: AlphaOptions::default(),
+            matching: Matching::Luminance,
+            renderer: Renderer::Ramp,
+            font: None,
+            raster: RasterOptions::default(),
+            html: HtmlOptions::default(),
+            output: OutputMode::Text,
+        }
+    }
+}
+
+impl AsciiImageBuilder {
+    pub fn table(mut self, table: &str) -> Self {
+        self.table = Some(table.chars().collect());
+        self.auto_table = None;
+        self
+    }
+
+    pub fn auto_table(mut self, candidates: &str, levels: usize) -> Self {
+        self.auto_table = Some((candidates.chars().collect(), levels));
+        self
+    }
+
+    pub fn scale(mut self, scaler: Scaler) -> Self {
+        self.scaler = scaler;
+        self
+    }
+
+    pub fn cell_aspect(mut self, cell_aspect: f64) -> Self {
+        self.cell_aspect = Some(cell_aspect);
+        self
+    }
+
+    pub fn filter(mut self, filter: FilterType) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    pub fn luminance(mut self, luminance: Luminance) -> Self {
+        self.luminance = luminance;
+        self
+    }
+
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    pub fn tone(mut self, tone: Tone) -> Self {
+        self.tone = tone;
+        self
+    }
+
+    pub fn edges(mut self, edges: EdgeOptions) -> Self {
+        self.edges = Some(edges);
+        self
+    }
+
+    pub fn alpha(mut self, alpha: AlphaOptions) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn matching(mut self, matching: Matching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    pub fn renderer(mut self, renderer: Renderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    pub fn font(mut self, data: Vec<u8>) -> Result<Self> {
+        self.font = Some((load_font(data.clone())?, data));
+        Ok(self)
+    }
+
+    pub fn font_size(mut self, font_size: u32) -> Self {
+        self.raster.font_size = font_size;
+        self
+    }
+
+    pub fn glyph_fit(mut self, fit: GlyphFit) -> Self {
+        self.raster.fit = fit;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        let (foreground, background) = theme.colors();
+
+        self.raster.foreground = foreground;
+        self.raster.background = background;
+        self
+    }
+
+    pub fn foreground(mut self, foreground: Rgb<u8>) -> Self {
+        self.raster.foreground = foreground;
+        self
+    }
+
+    pub fn background(mut self, background: Rgb<u8>) -> Self {
+        self.raster.background = background;
+        self
+    }
+
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.raster.transparent = transparent;
+        self
+    }
+
+    pub fn rgb(mut self, rgb: bool) -> Self {
+        self.raster.rgb = rgb;
+        self
+    }
+
+    pub fn html(mut self, html: HtmlOptions) -> Self {
+        self.html = html;
+        self
+    }
+
+    pub fn output(mut self, output: OutputMode) -> Self {
+        self.output = output;
+        self
+    }
+
+    fn with_font<T>(&self, f: impl FnOnce(&Font) -> Result<T>) -> Result<T> {
+        match &self.font {
+            Some((font, _)) => f(font),
+            None => f(shared_default_font()?),
+        }
+    }
+
+    pub fn build(&self, image: &DynamicImage) -> Result<AsciiImage> {
+        let aspect = match self.cell_aspect {
+            Some(aspect) => aspect,
+            None => self.with_font(|font| Ok(cell_aspect(font)))?,
+        };
+        let dimensions = self.scaler.dimensions(image.dimensions(), aspect);
+
+        let mut ascii_image = match self.alpha.background {
+            Some(background) => self.convert(&composite(image, background), dimensions)?,
+            None => self.convert(image, dimensions)?,
+        };
+
+        if let Some(threshold) = self.alpha.threshold {
+            ascii_image.clear_cells(empty_cells(image, dimensions, threshold));
+        }
+
+        Ok(ascii_image)
+    }
+
+    fn colored(&self) -> bool {
+        match self.output {
+            OutputMode::Text => false,
+            OutputMode::Ansi { .. } | OutputMode::Html => true,
+            OutputMode::Raster | OutputMode::Svg { .. } => self.raster.rgb,
+        }
+    }
+
+    fn light_on_dark(&self) -> bool {
+        let luma = |color: Rgb<u8>| color.to_luma().0[0];
+
+        matches!(self.output, OutputMode::Raster | OutputMode::Svg { .. })
+            && luma(self.raster.foreground) > luma(self.raster.background)
+    }
+
+    fn convert(&self, image: &DynamicImage, (width, height): (u32, u32)) -> Result<AsciiImage> {
+        let source = Source::new(image, self.luminance, self.linear);
+        let mut tone = self.tone;
+
+        if self.light_on_dark() {
+            tone.invert = !tone.invert;
+        }
+
+        if self.renderer != Renderer::Ramp {
+            if self.edges.is_some() {
+                return Err(Error::RendererConflict("edges"));
+            }
+            if self.matching != Matching::Luminance {
+                return Err(Error::RendererConflict("glyph matching"));
+            }
+            if self.table.is_some() {
+                return Err(Error::RendererConflict("an ascii table"));
+            }
+            if self.auto_table.is_some() {
+                return Err(Error::RendererConflict("a generated ascii table"));
+            }
+
+            let (cols, rows) = self.renderer.sub_cells();
+            let dimensions = (width * cols, height * rows);
+            let luma = source.luma(dimensions, self.filter);
+            let mut colors = source.colors(dimensions, self.filter);
+
+            self.tone.apply_colors(&luma, colors.pixels_mut());
+
+            let ink = if self.colored() && self.renderer != Renderer::Braille {
+                if self.dither != Dither::None {
+                    return Err(Error::DitherConflict("colored block output"));
+                }
+
+                None
+            } else {
+                Some(self.dither.ink_mask(&tone.apply(luma)))
+            };
+
+            let (chars, fg, bg) = self
+                .renderer
+                .render((width, height), ink.as_deref(), &colors);
+
+            return Ok(AsciiImage::from_chars(width, height, chars, fg, Some(bg)));
+        }
+
+        if self.matching != Matching::Luminance && self.dither != Dither::None {
+            return Err(Error::DitherConflict("glyph matching"));
+        }
+
+        let table = match &self.auto_table {
+            Some((candidates, levels)) => {
+                self.with_font(|font| Ok(generate_table(font, candidates, *levels)))?
+            }
+            None => self
+                .table
+                .clone()
+                .unwrap_or_else(|| DEFAULT_TABLE.chars().collect()),
+        };
+        let mut fg = source.cell_colors((width, height), self.filter);
+        let luma = source.luma((width, height), self.filter);
+
+        self.tone.apply_colors(&luma, fg.iter_mut());
+
+        let luma = tone.apply(luma);
+
+        let mut ascii_image = if self.matching == Matching::Luminance {
+            AsciiImage::from_luma(self.dither.apply(&luma, table.len()), fg, table)?
+        } else {
+            let samples = tone.apply(source.luma(
+                (width * SUB_CELL_WIDTH, height * SUB_CELL_HEIGHT),
+                self.filter,
+            ));
+            let chars = self.with_font(|font| {
+                self.matching
+                    .match_cells(&samples, &table, font, &self.raster)
+            })?;
+
+            AsciiImage::from_chars(width, height, chars, fg, None)
+        };
+
+        if let Some(edges) = &self.edges {
+            edges.apply(&luma, ascii_image.chars_mut());
+        }
+
+        Ok(ascii_image)
+    }
+
+    pub fn render_frames(
+        &self,
+        frames: &[(DynamicImage, Duration)],
+    ) -> Result<Vec<(Output, Duration)>> {
+        frames
+            .iter()
+            .map(|(image, delay)| Ok((self.render(image)?, *delay)))
+            .collect()
+    }
+
+    pub fn render_cast(
+        &self,
+        frames: &[(DynamicImage, Duration)],
+        frame_delay: Duration,
+    ) -> Result<String> {
+        let mut size = (0, 0);
+        let mut events = Vec::with_capacity(frames.len());
+
+        for (image, delay) in frames {
+            let ascii_image = self.build(image)?;
+            let text = match self.output {
+                OutputMode::Ansi { mode, background } => ascii_image.to_ansi(mode, background),
+                _ => ascii_image.to_string(),
+            };
+            let (width, height) = ascii_image.dimensions();
+
+            size = (size.0.max(width), size.1.max(height));
+            events.push((text, if delay.is_zero() { frame_delay } else { *delay }));
+        }
+
+        Ok(cast(size, &events))
+    }
+
+    pub fn render(&self, image: &DynamicImage) -> Result<Output> {
+        self.format(&self.build(image)?)
+    }
+
+    fn format(&self, ascii_image: &AsciiImage) -> Result<Output> {
+        match self.output {
+            OutputMode::Text => Ok(Output::Text(ascii_image.to_string())),
+
+            OutputMode::Ansi { mode, background } => {
+                Ok(Output::Text(ascii_image.to_ansi(mode, background)))
+            }
+
+            OutputMode::Raster => {
+                self.with_font(|font| Ok(Output::Image(ascii_image.rasterize(font, &self.raster))))
+            }
+
+            OutputMode::Svg { embed_font } => {
+                let (family, font_data) = match (&self.font, embed_font) {
+                    (None, true) => (Some(DEFAULT_FONT_FAMILY), Some(default_font_data()?)),
+                    (None, false) => (Some(DEFAULT_FONT_FAMILY), None),
+                    (Some((_, data)), true) => (Some(EMBEDDED_FONT_FAMILY), Some(&data[..])),
+                    (Some(_), false) => (None, None),
+                };
+
+                self.with_font(|font| {
+                    Ok(Output::Text(ascii_image.to_svg(
+                        font,
+                        &self.raster,
+                        family,
+                        font_data,
+                    )))
+                })
+            }
+
+            OutputMode::Html => Ok(Output::Text(ascii_image.to_html(&self.html))),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fmt::Write, time::Duration};
+
+pub(crate) fn cast((width, height): (u32, u32), frames: &[(String, Duration)]) -> String {
+    let mut cast = format!(
+        "{{\"version\": 2, \"width\": {}, \"height\": {}}}\n",
+        width, height
+    );
+    let mut time = Duration::default();
+
+    for (index, (text, delay)) in frames.iter().enumerate() {
+        let clear = if index == 0 { "\x1b[2J" } else { "" };
+
+        cast += &event(time, &format!("{}\x1b[H{}\x1b[0J", clear, text));
+        time += *delay;
+    }
+
+    cast + &event(time, "")
+}
+
+fn event(time: Duration, data: &str) -> String {
+    format!(
+        "[{:.6}, \"o\", {}]\n",
+        time.as_secs_f64(),
+        json_string(data)
+    )
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+
+    json.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            '\r' => json += "\\r",
+            '\t' => json += "\\t",
+            c if (c as u32) < 0x20 || c == '\x7f' => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("plain"), r#""plain""#);
+        assert_eq!(json_string(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(json_string("\r\n\t"), r#""\r\n\t""#);
+        assert_eq!(json_string("\x1b[0m\x7f"), r#""\u001b[0m\u007f""#);
+        assert_eq!(json_string("█é"), "\"█é\"");
+    }
+
+    #[test]
+    fn cast_events() {
+        let frames = [
+            (String::from("a"), Duration::from_millis(100)),
+            (String::from("b"), Duration::from_millis(250)),
+        ];
+        let cast = cast((2, 1), &frames);
+
+        assert_eq!(
+            cast.lines().collect::<Vec<&str>>(),
+            [
+                r#"{"version": 2, "width": 2, "height": 1}"#,
+                r#"[0.000000, "o", "\u001b[2J\u001b[Ha\u001b[0J"]"#,
+                r#"[0.100000, "o", "\u001b[Hb\u001b[0J"]"#,
+                r#"[0.350000, "o", ""]"#,
+            ]
+        );
+    }
+}
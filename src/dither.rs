@@ -0,0 +1,337 @@
+use crate::{Error, Result};
+use image::{GrayImage, ImageBuffer, Luma};
+use std::sync::OnceLock;
+
+const BLUE_NOISE_SIZE: u32 = 64;
+
+const FLOYD_STEINBERG: (f32, &[(i32, i32, f32)]) =
+    (16., &[(1, 0, 7.), (-1, 1, 3.), (0, 1, 5.), (1, 1, 1.)]);
+
+const ATKINSON: (f32, &[(i32, i32, f32)]) = (
+    8.,
+    &[
+        (1, 0, 1.),
+        (2, 0, 1.),
+        (-1, 1, 1.),
+        (0, 1, 1.),
+        (1, 1, 1.),
+        (0, 2, 1.),
+    ],
+);
+
+const JARVIS_JUDICE_NINKE: (f32, &[(i32, i32, f32)]) = (
+    48.,
+    &[
+        (1, 0, 7.),
+        (2, 0, 5.),
+        (-2, 1, 3.),
+        (-1, 1, 5.),
+        (0, 1, 7.),
+        (1, 1, 5.),
+        (2, 1, 3.),
+        (-2, 2, 1.),
+        (-1, 2, 3.),
+        (0, 2, 5.),
+        (1, 2, 3.),
+        (2, 2, 1.),
+    ],
+);
+
+const SIERRA: (f32, &[(i32, i32, f32)]) = (
+    32.,
+    &[
+        (1, 0, 5.),
+        (2, 0, 3.),
+        (-2, 1, 2.),
+        (-1, 1, 4.),
+        (0, 1, 5.),
+        (1, 1, 4.),
+        (2, 1, 2.),
+        (-1, 2, 2.),
+        (0, 2, 3.),
+        (1, 2, 2.),
+    ],
+);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Dither {
+    #[default]
+    None,
+    FloydSteinberg,
+    Atkinson,
+    JarvisJudiceNinke,
+    Sierra,
+    Bayer2,
+    Bayer4,
+    Bayer8,
+    BlueNoise,
+}
+
+impl Dither {
+    pub fn parse(dither: &str) -> Result<Dither> {
+        match dither {
+            "none" => Ok(Dither::None),
+            "floyd-steinberg" => Ok(Dither::FloydSteinberg),
+            "atkinson" => Ok(Dither::Atkinson),
+            "jarvis-judice-ninke" => Ok(Dither::JarvisJudiceNinke),
+            "sierra" => Ok(Dither::Sierra),
+            "bayer2" => Ok(Dither::Bayer2),
+            "bayer4" => Ok(Dither::Bayer4),
+            "bayer8" => Ok(Dither::Bayer8),
+            "blue-noise" => Ok(Dither::BlueNoise),
+            _ => Err(Error::UnsupportedDither(dither.into())),
+        }
+    }
+
+    pub fn apply(&self, image: &GrayImage, levels: usize) -> GrayImage {
+        match self {
+            Dither::None => image.clone(),
+            Dither::FloydSteinberg => diffuse(image, levels, FLOYD_STEINBERG),
+            Dither::Atkinson => diffuse(image, levels, ATKINSON),
+            Dither::JarvisJudiceNinke => diffuse(image, levels, JARVIS_JUDICE_NINKE),
+            Dither::Sierra => diffuse(image, levels, SIERRA),
+            Dither::Bayer2 => ordered(image, levels, 2, &bayer(2)),
+            Dither::Bayer4 => ordered(image, levels, 4, &bayer(4)),
+            Dither::Bayer8 => ordered(image, levels, 8, &bayer(8)),
+            Dither::BlueNoise => ordered(image, levels, BLUE_NOISE_SIZE, blue_noise()),
+        }
+    }
+
+    pub fn ink_mask(&self, image: &GrayImage) -> Vec<bool> {
+        let threshold = if *self == Dither::None { 128 } else { 255 };
+
+        self.apply(image, 2)
+            .pixels()
+            .map(|p| p.0[0] < threshold)
+            .collect()
+    }
+}
+
+fn diffuse(
+    image: &GrayImage,
+    levels: usize,
+    (divisor, kernel): (f32, &[(i32, i32, f32)]),
+) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let mut values: Vec<f32> = image.pixels().map(|p| p.0[0] as f32).collect();
+    let mut output: GrayImage = ImageBuffer::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = values[(y * width + x) as usize];
+            let index = nearest_level(value, levels);
+            let error = value - level_value(index, levels);
+
+            output.put_pixel(x, y, Luma([level_luma(index, levels)]));
+
+            for (dx, dy, weight) in kernel {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+
+                if nx >= 0 && nx < width as i32 && ny < height as i32 {
+                    values[(ny as u32 * width + nx as u32) as usize] += error * weight / divisor;
+                }
+            }
+        }
+    }
+
+    output
+}
+
+fn ordered(image: &GrayImage, levels: usize, size: u32, thresholds: &[f32]) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let mut output: GrayImage = ImageBuffer::new(width, height);
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let threshold = thresholds[((y % size) * size + x % size) as usize];
+        let index = if levels < 2 {
+            0
+        } else {
+            ((pixel.0[0] as f32 / 255. * (levels - 1) as f32 + threshold).floor() as usize)
+                .min(levels - 1)
+        };
+
+        output.put_pixel(x, y, Luma([level_luma(index, levels)]));
+    }
+
+    output
+}
+
+fn bayer(size: u32) -> Vec<f32> {
+    let mut matrix = vec![0u32];
+    let mut n = 1;
+
+    while n < size {
+        let mut next = vec![0; (n * n * 4) as usize];
+
+        for y in 0..n {
+            for x in 0..n {
+                let m = matrix[(y * n + x) as usize] * 4;
+
+                next[(y * 2 * n + x) as usize] = m;
+                next[(y * 2 * n + x + n) as usize] = m + 2;
+                next[((y + n) * 2 * n + x) as usize] = m + 3;
+                next[((y + n) * 2 * n + x + n) as usize] = m + 1;
+            }
+        }
+
+        matrix = next;
+        n *= 2;
+    }
+
+    matrix
+        .into_iter()
+        .map(|m| (m as f32 + 0.5) / (size * size) as f32)
+        .collect()
+}
+
+fn blue_noise() -> &'static [f32] {
+    static TEXTURE: OnceLock<Vec<f32>> = OnceLock::new();
+
+    TEXTURE.get_or_init(|| void_and_cluster(BLUE_NOISE_SIZE, 1.5))
+}
+
+fn void_and_cluster(size: u32, sigma: f32) -> Vec<f32> {
+    let area = (size * size) as usize;
+    let half = size as i32 / 2;
+
+    let falloff: Vec<f32> = (0..area)
+        .map(|i| {
+            let wrap = |d: i32| if d >= half { d - size as i32 } else { d };
+            let dx = wrap(i as i32 % size as i32) as f32;
+            let dy = wrap(i as i32 / size as i32) as f32;
+
+            (-(dx * dx + dy * dy) / (2. * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let mut pattern = vec![false; area];
+    let mut energy = vec![0f32; area];
+
+    let update = |energy: &mut Vec<f32>, index: usize, sign: f32| {
+        let (px, py) = (index as u32 % size, index as u32 / size);
+
+        for (i, e) in energy.iter_mut().enumerate() {
+            let dx = (i as u32 % size + size - px) % size;
+            let dy = (i as u32 / size + size - py) % size;
+
+            *e += sign * falloff[(dy * size + dx) as usize];
+        }
+    };
+
+    let tightest_cluster = |pattern: &[bool], energy: &[f32]| {
+        (0..area)
+            .filter(|i| pattern[*i])
+            .max_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+
+    let largest_void = |pattern: &[bool], energy: &[f32]| {
+        (0..area)
+            .filter(|i| !pattern[*i])
+            .min_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap()
+    };
+
+    let mut seed = 0x2545_f491_u32;
+    let mut ones = 0;
+
+    while ones < area / 10 {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+
+        let index = seed as usize % area;
+
+        if !pattern[index] {
+            pattern[index] = true;
+            update(&mut energy, index, 1.);
+            ones += 1;
+        }
+    }
+
+    loop {
+        let cluster = tightest_cluster(&pattern, &energy);
+        pattern[cluster] = false;
+        update(&mut energy, cluster, -1.);
+
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        update(&mut energy, void, 1.);
+
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; area];
+    let (initial_pattern, initial_energy) = (pattern.clone(), energy.clone());
+
+    for rank in (0..ones).rev() {
+        let cluster = tightest_cluster(&pattern, &energy);
+        pattern[cluster] = false;
+        update(&mut energy, cluster, -1.);
+        ranks[cluster] = rank;
+    }
+
+    pattern = initial_pattern;
+    energy = initial_energy;
+
+    for rank in ones..area {
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        update(&mut energy, void, 1.);
+        ranks[void] = rank;
+    }
+
+    ranks
+        .into_iter()
+        .map(|rank| (rank as f32 + 0.5) / area as f32)
+        .collect()
+}
+
+fn nearest_level(value: f32, levels: usize) -> usize {
+    if levels < 2 {
+        return 0;
+    }
+
+    (value / 255. * (levels - 1) as f32)
+        .round()
+        .max(0.)
+        .min((levels - 1) as f32) as usize
+}
+
+fn level_value(index: usize, levels: usize) -> f32 {
+    if levels < 2 {
+        0.
+    } else {
+        index as f32 * 255. / (levels - 1) as f32
+    }
+}
+
+fn level_luma(index: usize, levels: usize) -> u8 {
+    if index + 1 >= levels {
+        255
+    } else {
+        ((index as f32 + 0.5) * 255. / (levels - 1) as f32).round() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bayer_2x2_thresholds() {
+        assert_eq!(bayer(2), [0.125, 0.625, 0.875, 0.375]);
+    }
+
+    #[test]
+    fn bayer_4x4_order() {
+        let order: Vec<u32> = bayer(4).iter().map(|t| (t * 16. - 0.5) as u32).collect();
+
+        assert_eq!(
+            order,
+            [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5]
+        );
+    }
+}
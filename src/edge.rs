@@ -0,0 +1,124 @@
+use crate::{Error, Result};
+use image::GrayImage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EdgeKernel {
+    #[default]
+    Sobel,
+    Scharr,
+}
+
+impl EdgeKernel {
+    pub fn parse(kernel: &str) -> Result<EdgeKernel> {
+        match kernel {
+            "sobel" => Ok(EdgeKernel::Sobel),
+            "scharr" => Ok(EdgeKernel::Scharr),
+            _ => Err(Error::UnsupportedEdgeKernel(kernel.into())),
+        }
+    }
+
+    fn weights(&self) -> ([f32; 3], f32) {
+        match self {
+            EdgeKernel::Sobel => ([1., 2., 1.], 4.),
+            EdgeKernel::Scharr => ([3., 10., 3.], 16.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EdgeOptions {
+    pub kernel: EdgeKernel,
+    pub threshold: f32,
+    pub blank: bool,
+}
+
+impl Default for EdgeOptions {
+    fn default() -> Self {
+        EdgeOptions {
+            kernel: EdgeKernel::Sobel,
+            threshold: 0.25,
+            blank: false,
+        }
+    }
+}
+
+impl EdgeOptions {
+    pub(crate) fn apply(&self, luma: &GrayImage, chars: &mut [char]) {
+        let (width, height) = luma.dimensions();
+        let (weights, norm) = self.kernel.weights();
+        let sample = |x: i64, y: i64| {
+            let x = x.clamp(0, width as i64 - 1) as u32;
+            let y = y.clamp(0, height as i64 - 1) as u32;
+
+            luma.get_pixel(x, y).0[0] as f32 / 255.
+        };
+
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let (mut gx, mut gy) = (0., 0.);
+
+                for (i, weight) in (-1..=1).zip(weights.iter()) {
+                    gx += weight * (sample(x + 1, y + i) - sample(x - 1, y + i));
+                    gy += weight * (sample(x + i, y + 1) - sample(x + i, y - 1));
+                }
+
+                let (gx, gy) = (gx / norm, gy / norm);
+                let c = &mut chars[(y * width as i64 + x) as usize];
+
+                if (gx * gx + gy * gy).sqrt() >= self.threshold {
+                    *c = direction(gx, gy);
+                } else if self.blank {
+                    *c = ' ';
+                }
+            }
+        }
+    }
+}
+
+fn direction(gx: f32, gy: f32) -> char {
+    let angle = gy.atan2(gx).to_degrees().rem_euclid(180.);
+
+    match angle {
+        a if !(22.5..157.5).contains(&a) => '|',
+        a if a < 67.5 => '/',
+        a if a < 112.5 && gy > 0. => '_',
+        a if a < 112.5 => '-',
+        _ => '\\',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma};
+
+    #[test]
+    fn direction_follows_the_edge() {
+        assert_eq!(direction(1., 0.), '|');
+        assert_eq!(direction(-1., 0.), '|');
+        assert_eq!(direction(0., 1.), '_');
+        assert_eq!(direction(0., -1.), '-');
+        assert_eq!(direction(1., 1.), '/');
+        assert_eq!(direction(-1., -1.), '/');
+        assert_eq!(direction(-1., 1.), '\\');
+        assert_eq!(direction(1., -1.), '\\');
+    }
+
+    #[test]
+    fn vertical_step() {
+        let luma = ImageBuffer::from_fn(4, 1, |x, _| Luma([if x < 2 { 0 } else { 255 }]));
+        let mut chars = vec!['a'; 4];
+
+        EdgeOptions::default().apply(&luma, &mut chars);
+        assert_eq!(chars, ['a', '|', '|', 'a']);
+
+        let blank = EdgeOptions {
+            blank: true,
+            ..EdgeOptions::default()
+        };
+        let mut chars = vec!['a'; 4];
+
+        blank.apply(&luma, &mut chars);
+        assert_eq!(chars, [' ', '|', '|', ' ']);
+    }
+}
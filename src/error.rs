@@ -0,0 +1,121 @@
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("can not find file '{}'", .0.display())]
+    NotFound(PathBuf),
+
+    #[error("invalid scale value '{0}'")]
+    InvalidScale(String),
+
+    #[error("can't fit to the terminal size without a terminal")]
+    NoTerminal,
+
+    #[error("invalid cell aspect ratio '{0}'")]
+    InvalidCellAspect(String),
+
+    #[error("unsupported filter type '{0}'")]
+    UnsupportedFilter(String),
+
+    #[error("unsupported ansi mode '{0}'")]
+    UnsupportedAnsiMode(String),
+
+    #[error("unsupported dither mode '{0}'")]
+    UnsupportedDither(String),
+
+    #[error("unsupported edge kernel '{0}'")]
+    UnsupportedEdgeKernel(String),
+
+    #[error("unsupported equalization '{0}'")]
+    UnsupportedEqualize(String),
+
+    #[error("unsupported luminance weights '{0}'")]
+    UnsupportedLuminance(String),
+
+    #[error("unsupported matching mode '{0}'")]
+    UnsupportedMatching(String),
+
+    #[error("unsupported renderer '{0}'")]
+    UnsupportedRenderer(String),
+
+    #[error("the ascii table must contain at least one character")]
+    EmptyTable,
+
+    #[error("invalid brightness '{0}'")]
+    InvalidBrightness(String),
+
+    #[error("invalid contrast '{0}'")]
+    InvalidContrast(String),
+
+    #[error("invalid gamma '{0}'")]
+    InvalidGamma(String),
+
+    #[error("invalid levels '{0}', expected black:white")]
+    InvalidLevels(String),
+
+    #[error("invalid clip percentage '{0}'")]
+    InvalidClip(String),
+
+    #[error("invalid alpha threshold '{0}'")]
+    InvalidAlphaThreshold(String),
+
+    #[error("invalid edge threshold '{0}'")]
+    InvalidEdgeThreshold(String),
+
+    #[error("invalid clahe tile size '{0}'")]
+    InvalidClaheTile(String),
+
+    #[error("invalid clahe clip limit '{0}'")]
+    InvalidClaheClip(String),
+
+    #[error("invalid number of ascii table levels '{0}'")]
+    InvalidTableLevels(String),
+
+    #[error("invalid font size '{0}'")]
+    InvalidFontSize(String),
+
+    #[error("can't parse font file: {0}")]
+    Font(&'static str),
+
+    #[error("unsupported theme '{0}'")]
+    UnsupportedTheme(String),
+
+    #[error("invalid color '{0}'")]
+    InvalidColor(String),
+
+    #[error("unsupported glyph fit '{0}'")]
+    UnsupportedGlyphFit(String),
+
+    #[error("invalid line height '{0}'")]
+    InvalidLineHeight(String),
+
+    #[error("invalid frame delay '{0}'")]
+    InvalidFrameDelay(String),
+
+    #[error("unsupported image format '{0}'")]
+    UnsupportedFormat(String),
+
+    #[error("writing an image to stdout requires --format")]
+    MissingFormat,
+
+    #[error("only the ramp renderer supports {0}")]
+    RendererConflict(&'static str),
+
+    #[error("dithering can't be combined with {0}")]
+    DitherConflict(&'static str),
+
+    #[error("animated raster output must be a gif, got '{}'", .0.display())]
+    AnimatedOutput(PathBuf),
+
+    #[error("failed to process image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("failed to access file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("failed to handle ctrl-c: {0}")]
+    CtrlC(#[from] ctrlc::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
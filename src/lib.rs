@@ -0,0 +1,69 @@
+mod alpha;
+mod animation;
+mod ansi;
+mod ascii_image;
+mod builder;
+mod cast;
+mod dither;
+mod edge;
+mod error;
+mod luminance;
+mod matching;
+mod player;
+mod raster;
+mod renderer;
+mod scaler;
+mod table;
+mod tone;
+
+pub use alpha::AlphaOptions;
+pub use animation::{load_frames, open_frames, write_gif};
+pub use ansi::AnsiMode;
+pub use ascii_image::{AsciiImage, HtmlOptions};
+pub use builder::{AsciiImageBuilder, Output, OutputMode};
+pub use dither::Dither;
+pub use edge::{EdgeKernel, EdgeOptions};
+pub use error::{Error, Result};
+pub use luminance::Luminance;
+pub use matching::{Matching, SUB_CELL_HEIGHT, SUB_CELL_WIDTH};
+pub use player::{play, PlayOptions};
+pub use raster::{cell_aspect, cell_size, parse_color, GlyphFit, RasterOptions, Theme};
+pub use renderer::Renderer;
+pub use scaler::{parse_filter, Scaler};
+pub use table::{coverage, generate_table, PRINTABLE_ASCII};
+pub use tone::{Equalize, Tone};
+
+pub use fontdue::Font;
+pub use image::imageops::FilterType;
+
+use fontdue::FontSettings;
+use packer::Packer;
+use std::sync::OnceLock;
+
+#[derive(Packer)]
+#[packer(source = "assets/consolas.ttf")]
+struct Assets;
+
+pub(crate) fn default_font_data() -> Result<&'static [u8]> {
+    Assets::get("assets/consolas.ttf").ok_or(Error::Font("missing embedded font"))
+}
+
+pub fn default_font() -> Result<Font> {
+    Font::from_bytes(default_font_data()?, FontSettings::default()).map_err(Error::Font)
+}
+
+pub(crate) fn shared_default_font() -> Result<&'static Font> {
+    static FONT: OnceLock<Font> = OnceLock::new();
+
+    if let Some(font) = FONT.get() {
+        return Ok(font);
+    }
+
+    let font = default_font()?;
+
+    Ok(FONT.get_or_init(|| font))
+}
+
+pub fn load_font(data: Vec<u8>) -> Result<Font> {
+    Font::from_bytes(data, FontSettings::default()).map_err(Error::Font)
+}
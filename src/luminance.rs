@@ -0,0 +1,155 @@
+use crate::{ascii_image::average_colors, scaler::resize_to, Error, Result};
+use image::{imageops::FilterType, DynamicImage, GrayImage, ImageBuffer, Luma, Rgb, RgbImage};
+use std::sync::OnceLock;
+
+type LinearRgbImage = ImageBuffer<Rgb<f32>, Vec<f32>>;
+type LinearGrayImage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Luminance {
+    #[default]
+    Rec709,
+    Rec601,
+    Custom([f32; 3]),
+}
+
+impl Luminance {
+    pub fn parse(luminance: &str) -> Result<Luminance> {
+        match luminance {
+            "rec709" => Ok(Luminance::Rec709),
+            "rec601" => Ok(Luminance::Rec601),
+            _ => {
+                let weights = luminance
+                    .split(',')
+                    .map(|w| w.trim().parse::<f32>().ok().filter(|w| *w >= 0.))
+                    .collect::<Option<Vec<f32>>>();
+
+                match weights.as_deref() {
+                    Some(&[r, g, b]) if r + g + b > 0. => {
+                        let sum = r + g + b;
+
+                        Ok(Luminance::Custom([r / sum, g / sum, b / sum]))
+                    }
+                    _ => Err(Error::UnsupportedLuminance(luminance.into())),
+                }
+            }
+        }
+    }
+
+    pub fn weights(&self) -> [f32; 3] {
+        match self {
+            Luminance::Rec709 => [0.2126, 0.7152, 0.0722],
+            Luminance::Rec601 => [0.299, 0.587, 0.114],
+            Luminance::Custom(weights) => *weights,
+        }
+    }
+
+    fn apply(&self, [r, g, b]: [f32; 3]) -> f32 {
+        let [wr, wg, wb] = self.weights();
+
+        r * wr + g * wg + b * wb
+    }
+}
+
+pub(crate) enum Source {
+    Gamma {
+        luma: GrayImage,
+        colors: RgbImage,
+    },
+    Linear {
+        luma: LinearGrayImage,
+        colors: LinearRgbImage,
+    },
+}
+
+impl Source {
+    pub(crate) fn new(image: &DynamicImage, luminance: Luminance, linear: bool) -> Source {
+        let colors = image.to_rgb8();
+
+        if !linear {
+            let luma = ImageBuffer::from_fn(colors.width(), colors.height(), |x, y| {
+                let rgb = colors.get_pixel(x, y).0.map(|c| c as f32);
+
+                Luma([luminance.apply(rgb).round().min(255.) as u8])
+            });
+
+            return Source::Gamma { luma, colors };
+        }
+
+        let colors: LinearRgbImage =
+            ImageBuffer::from_fn(colors.width(), colors.height(), |x, y| {
+                Rgb(colors.get_pixel(x, y).0.map(to_linear))
+            });
+        let luma = ImageBuffer::from_fn(colors.width(), colors.height(), |x, y| {
+            Luma([luminance.apply(colors.get_pixel(x, y).0)])
+        });
+
+        Source::Linear { luma, colors }
+    }
+
+    pub(crate) fn luma(&self, dimensions: (u32, u32), filter: FilterType) -> GrayImage {
+        match self {
+            Source::Gamma { luma, .. } => resize_to(luma, dimensions, filter),
+
+            Source::Linear { luma, .. } => {
+                let luma = resize_to(luma, dimensions, filter);
+
+                ImageBuffer::from_fn(luma.width(), luma.height(), |x, y| {
+                    Luma([to_srgb(luma.get_pixel(x, y).0[0])])
+                })
+            }
+        }
+    }
+
+    pub(crate) fn colors(&self, dimensions: (u32, u32), filter: FilterType) -> RgbImage {
+        match self {
+            Source::Gamma { colors, .. } => resize_to(colors, dimensions, filter),
+
+            Source::Linear { colors, .. } => {
+                let colors = resize_to(colors, dimensions, filter);
+
+                ImageBuffer::from_fn(colors.width(), colors.height(), |x, y| {
+                    Rgb(colors.get_pixel(x, y).0.map(to_srgb))
+                })
+            }
+        }
+    }
+
+    pub(crate) fn cell_colors(&self, dimensions: (u32, u32), filter: FilterType) -> Vec<Rgb<u8>> {
+        match self {
+            Source::Gamma { colors, .. } => average_colors(colors, dimensions),
+            Source::Linear { .. } => self.colors(dimensions, filter).pixels().copied().collect(),
+        }
+    }
+}
+
+fn to_linear(c: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        let mut table = [0.; 256];
+
+        for (i, value) in table.iter_mut().enumerate() {
+            let c = i as f32 / 255.;
+
+            *value = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+
+        table
+    })[c as usize]
+}
+
+fn to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0., 1.);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    };
+
+    (c * 255.).round() as u8
+}
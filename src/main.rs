@@ -1,174 +1,23 @@
-use clap::{App, Arg};
-use fontdue::{Font, FontSettings};
-use image::{
-    imageops::{resize, FilterType},
-    GenericImage, GenericImageView, GrayImage, ImageBuffer, Luma, Pixel,
+use ascii::{
+    load_frames, open_frames, parse_color, parse_filter, play, write_gif, AlphaOptions, AnsiMode,
+    AsciiImage, Dither, EdgeKernel, EdgeOptions, Equalize, Error, GlyphFit, HtmlOptions, Luminance,
+    Matching, Output, OutputMode, PlayOptions, Renderer, Result, Scaler, Theme, Tone,
+    PRINTABLE_ASCII,
 };
-use packer::Packer;
+use clap::{App, Arg};
+use image::ImageFormat;
 use std::{
-    collections::HashMap,
-    fmt::{self, Display},
     fs,
-    iter::FromIterator,
-    path::PathBuf,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
-type RasterCache = HashMap<char, ImageBuffer<Luma<u8>, Vec<u8>>>;
-
-#[derive(Packer)]
-#[packer(source = "assets/consolas.ttf")]
-struct Assets;
-
-struct AsciiImage(GrayImage, Vec<char>);
-
-impl AsciiImage {
-    fn rasterize(&self, font: Font, px: u32) -> ImageBuffer<Luma<u8>, Vec<u8>> {
-        let cache: RasterCache = HashMap::from_iter(self.1.iter().map(|c| {
-            let (metrics, bitmap) = font.rasterize(*c, (px - 1) as f32);
-
-            assert!(
-                metrics.height <= px as usize,
-                "rastered image won't fit in bounding box '{}'",
-                c
-            );
-
-            assert!(
-                metrics.width <= px as usize,
-                "rastered image won't fit in bounding box '{}'",
-                c
-            );
-
-            let mut img: ImageBuffer<_, _> = ImageBuffer::from_pixel(px, px, Luma([255]));
-
-            let dx = (px as usize - metrics.width) >> 1;
-            let dy = (px as usize - metrics.height) >> 1;
-
-            let mut bitmap = bitmap.into_iter();
-
-            for y in dy..metrics.height + dy {
-                for x in dx..metrics.width + dx {
-                    img.put_pixel(
-                        x as u32,
-                        y as u32,
-                        Luma([255 - bitmap.next().expect("rasterized image buffer too small")]),
-                    )
-                }
-            }
-
-            (*c, img)
-        }));
-
-        let mut img: ImageBuffer<Luma<u8>, _> =
-            ImageBuffer::new(self.0.width() * px, self.0.height() * px);
-
-        for iy in 0..self.0.height() {
-            for ix in 0..self.0.width() {
-                let mut sub_img = img.sub_image(ix * px, iy * px, px, px);
-                let c = self.1[(self.0.get_pixel(ix, iy).0[0] as f64 / 255.
-                    * (self.1.len() - 1) as f64)
-                    .trunc() as usize];
-
-                let raster = cache.get(&c).unwrap();
-
-                for sy in 0..px {
-                    for sx in 0..px {
-                        sub_img.put_pixel(sx, sy, *raster.get_pixel(sx, sy));
-                    }
-                }
-            }
-        }
-
-        img
-    }
-}
-
-impl Display for AsciiImage {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let text = self
-            .0
-            .rows()
-            .map(|row| {
-                row.map(|luma| {
-                    self.1[(luma.0[0] as f64 / 255. * (self.1.len() - 1) as f64).trunc() as usize]
-                        .to_string()
-                        .repeat(2)
-                })
-                .collect::<String>()
-            })
-            .collect::<Vec<String>>()
-            .join("\r\n");
-
-        write!(f, "{}", text)
-    }
-}
-
-struct Scaler(Option<u32>, Option<u32>);
-
-impl Scaler {
-    fn parse(scale: &str) -> Scaler {
-        let sizes: Vec<Option<u32>> = scale
-            .split(":")
-            .map(|s| match s {
-                "_" => None,
-                num @ _ => Some(
-                    num.parse::<u32>()
-                        .expect("couldn't parse scale value as u32"),
-                ),
-            })
-            .collect();
-
-        if sizes.len() == 1 {
-            Self(sizes[0], sizes[0])
-        } else {
-            assert!(sizes.len() == 2, "invalid scale value");
-            Self(sizes[0], sizes[1])
-        }
-    }
-
-    fn scale<I: GenericImageView>(
-        &self,
-        image: &I,
-        filter: FilterType,
-    ) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
-    where
-        I::Pixel: 'static,
-        <I::Pixel as Pixel>::Subpixel: 'static,
-    {
-        match (self.0, self.1) {
-            (Some(width), Some(height)) => resize(image, width, height, filter),
-
-            (Some(width), None) => {
-                let height = width as f64 / image.width() as f64 * image.height() as f64;
-
-                resize(image, width, height as u32, filter)
-            }
-
-            (None, Some(height)) => {
-                let width = height as f64 / image.height() as f64 * image.width() as f64;
-                resize(image, width as u32, height, filter)
-            }
-
-            (None, None) => ImageBuffer::from_raw(
-                image.width(),
-                image.height(),
-                image
-                    .pixels()
-                    .map(|(_, _, pixel)| {
-                        pixel
-                            .channels()
-                            .iter()
-                            .map(|p| *p)
-                            .collect::<Vec<<I::Pixel as Pixel>::Subpixel>>()
-                    })
-                    .flatten()
-                    .collect(),
-            )
-            .expect("failed to build image buffer"),
-        }
-    }
-}
-
-fn main() {
+fn run() -> Result<()> {
     let matches = App::new("ascii")
         .version("1.0")
         .author("QuantumCoded github")
@@ -177,21 +26,27 @@ fn main() {
             Arg::with_name("INPUT")
                 .required(true)
                 .index(1)
-                .help("The image to convert"),
+                .help("The image to convert or - for stdin, animated gifs and pngs convert every frame"),
         )
         .arg(
             Arg::with_name("OUTPUT")
-                .required(true)
+                .required_unless("play")
                 .index(2)
-                .help("The output ascii file"),
+                .help("The output ascii file or - for stdout"),
         )
         .arg(
             Arg::with_name("scale")
                 .short("s")
                 .long("scale")
-                .help("The resolution to scale the image to")
+                .help("The size to scale the image to as width:height before the cell aspect is applied, or fit for the terminal")
                 .value_name("scale"),
         )
+        .arg(
+            Arg::with_name("cell aspect")
+                .long("cell-aspect")
+                .help("The height to width ratio of a character cell, defaults to the font's")
+                .value_name("ratio"),
+        )
         .arg(
             Arg::with_name("filter")
                 .long("filter")
@@ -203,12 +58,168 @@ fn main() {
                 .possible_value("lanczos3")
                 .default_value("lanczos3"),
         )
+        .arg(
+            Arg::with_name("renderer")
+                .long("renderer")
+                .help("Draws cells with the ascii table or with unicode block elements")
+                .possible_value("ramp")
+                .possible_value("half-block")
+                .possible_value("quadrant")
+                .possible_value("sextant")
+                .possible_value("braille")
+                .default_value("ramp"),
+        )
+        .arg(
+            Arg::with_name("dither")
+                .long("dither")
+                .help("The dithering to use when mapping pixels to characters")
+                .possible_value("none")
+                .possible_value("floyd-steinberg")
+                .possible_value("atkinson")
+                .possible_value("jarvis-judice-ninke")
+                .possible_value("sierra")
+                .possible_value("bayer2")
+                .possible_value("bayer4")
+                .possible_value("bayer8")
+                .possible_value("blue-noise")
+                .default_value("none"),
+        )
+        .arg(
+            Arg::with_name("alpha background")
+                .long("alpha-background")
+                .help("Composites transparent images over this color as hex or r,g,b")
+                .value_name("color"),
+        )
+        .arg(
+            Arg::with_name("alpha threshold")
+                .long("alpha-threshold")
+                .help("Leaves cells with an average alpha from 0 to 255 below this empty")
+                .value_name("alpha"),
+        )
+        .arg(
+            Arg::with_name("luminance")
+                .long("luminance")
+                .help("The luminance weights as rec709, rec601 or a custom r,g,b mix")
+                .default_value("rec709")
+                .value_name("weights"),
+        )
+        .arg(
+            Arg::with_name("linear")
+                .long("linear")
+                .help("Converts and resizes the image in linear light instead of sRGB"),
+        )
+        .arg(
+            Arg::with_name("equalize")
+                .long("equalize")
+                .help("Equalizes the brightness histogram globally or per tile with clahe")
+                .possible_value("none")
+                .possible_value("global")
+                .possible_value("clahe")
+                .default_value("none"),
+        )
+        .arg(
+            Arg::with_name("clahe tile")
+                .long("clahe-tile")
+                .help("The size of the clahe tiles in samples")
+                .value_name("size"),
+        )
+        .arg(
+            Arg::with_name("clahe clip")
+                .long("clahe-clip")
+                .help("The clahe clip limit as a multiple of the average histogram bin")
+                .value_name("limit"),
+        )
+        .arg(
+            Arg::with_name("brightness")
+                .long("brightness")
+                .help("Shifts the brightness by -1 to 1 before mapping to characters")
+                .value_name("amount")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("contrast")
+                .long("contrast")
+                .help("Multiplies the contrast around middle gray")
+                .value_name("factor"),
+        )
+        .arg(
+            Arg::with_name("gamma")
+                .long("gamma")
+                .help("Applies a gamma correction, values above 1 brighten the midtones")
+                .value_name("gamma"),
+        )
+        .arg(
+            Arg::with_name("levels")
+                .long("levels")
+                .help("The black and white points from 0 to 255 as black:white")
+                .value_name("levels"),
+        )
+        .arg(
+            Arg::with_name("invert")
+                .long("invert")
+                .help("Inverts the brightness before mapping to characters"),
+        )
+        .arg(
+            Arg::with_name("auto stretch")
+                .long("auto-stretch")
+                .help("Stretches the brightness to the full range of the histogram"),
+        )
+        .arg(
+            Arg::with_name("clip")
+                .long("clip")
+                .help("Stretches the brightness ignoring this percentage of the darkest and lightest pixels")
+                .value_name("percent"),
+        )
+        .arg(
+            Arg::with_name("edges")
+                .long("edges")
+                .help("Draws edges with directional line characters using this gradient kernel")
+                .possible_value("sobel")
+                .possible_value("scharr")
+                .value_name("kernel"),
+        )
+        .arg(
+            Arg::with_name("edge threshold")
+                .long("edge-threshold")
+                .requires("edges")
+                .help("The gradient magnitude from 0 to 1 a cell needs to be drawn as an edge")
+                .value_name("threshold"),
+        )
+        .arg(
+            Arg::with_name("edge blank")
+                .long("edge-blank")
+                .requires("edges")
+                .help("Leaves cells that aren't edges blank instead of using the ascii table"),
+        )
+        .arg(
+            Arg::with_name("match")
+                .long("match")
+                .help("How each cell is matched to a character")
+                .possible_value("luminance")
+                .possible_value("sse")
+                .possible_value("ssim")
+                .default_value("luminance"),
+        )
         .arg(
             Arg::with_name("ascii table")
                 .short("t")
                 .long("table")
-                .help("The ascii character to use ordered from darkest to lightest")
-                .default_value("@%#*+=-:. "),
+                .help("The ascii characters to use ordered from darkest to lightest, defaults to '@%#*+=-:. '")
+                .value_name("characters"),
+        )
+        .arg(
+            Arg::with_name("auto table")
+                .long("auto-table")
+                .conflicts_with("ascii table")
+                .help("Generates an ascii table with this many characters from the font's glyph coverage")
+                .value_name("levels"),
+        )
+        .arg(
+            Arg::with_name("table candidates")
+                .long("table-candidates")
+                .requires("auto table")
+                .help("The characters the generated ascii table is picked from")
+                .value_name("characters"),
         )
         .arg(
             Arg::with_name("raster")
@@ -216,6 +227,80 @@ fn main() {
                 .long("raster")
                 .help("Changes the output type from a text file to a rastered image"),
         )
+        .arg(
+            Arg::with_name("play")
+                .long("play")
+                .conflicts_with_all(&["OUTPUT", "raster", "svg", "html"])
+                .help("Plays the converted frames in the terminal instead of writing a file"),
+        )
+        .arg(
+            Arg::with_name("loop")
+                .long("loop")
+                .requires("play")
+                .help("Repeats the animation until interrupted"),
+        )
+        .arg(
+            Arg::with_name("cast")
+                .long("cast")
+                .conflicts_with_all(&["raster", "svg", "html", "play"])
+                .help("Changes the output type from a text file to an asciinema cast"),
+        )
+        .arg(
+            Arg::with_name("frame delay")
+                .long("frame-delay")
+                .help("The delay in milliseconds of frames that don't have their own")
+                .value_name("ms"),
+        )
+        .arg(
+            Arg::with_name("svg")
+                .long("svg")
+                .conflicts_with("raster")
+                .help("Changes the output type from a text file to an svg image"),
+        )
+        .arg(
+            Arg::with_name("svg embed font")
+                .long("svg-embed-font")
+                .requires("svg")
+                .help("Embeds the default font in the svg image"),
+        )
+        .arg(
+            Arg::with_name("html")
+                .long("html")
+                .conflicts_with_all(&["raster", "svg"])
+                .help("Changes the output type from a text file to a colored html page"),
+        )
+        .arg(
+            Arg::with_name("html fragment")
+                .long("html-fragment")
+                .requires("html")
+                .help("Writes only the <pre> block instead of a full html page"),
+        )
+        .arg(
+            Arg::with_name("html background")
+                .long("html-background")
+                .requires("html")
+                .help("Colors the cell background instead of the character"),
+        )
+        .arg(
+            Arg::with_name("html font family")
+                .long("html-font-family")
+                .requires("html")
+                .help("The css font family of the html output")
+                .value_name("family"),
+        )
+        .arg(
+            Arg::with_name("html line height")
+                .long("html-line-height")
+                .requires("html")
+                .help("The css line height of the html output")
+                .value_name("height"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .help("The format of rastered images, required when writing them to stdout")
+                .value_name("format"),
+        )
         .arg(
             Arg::with_name("font")
                 .long("font")
@@ -228,74 +313,440 @@ fn main() {
                 .help("The height of the font in pixels")
                 .value_name("font size"),
         )
+        .arg(
+            Arg::with_name("glyph fit")
+                .long("glyph-fit")
+                .help("How glyphs that don't fit in a character cell are rastered")
+                .possible_value("clip")
+                .possible_value("shrink")
+                .possible_value("widen")
+                .default_value("clip"),
+        )
+        .arg(
+            Arg::with_name("theme")
+                .long("theme")
+                .help("The colors of the rasterized characters and background")
+                .possible_value("light")
+                .possible_value("terminal")
+                .default_value("light"),
+        )
+        .arg(
+            Arg::with_name("foreground")
+                .long("foreground")
+                .help("The color of the rasterized characters as hex or r,g,b")
+                .value_name("color"),
+        )
+        .arg(
+            Arg::with_name("background")
+                .long("background")
+                .help("The background color of the rastered image as hex or r,g,b")
+                .value_name("color"),
+        )
+        .arg(
+            Arg::with_name("transparent")
+                .long("transparent")
+                .help("Rasters the characters onto a transparent background"),
+        )
         .arg(
             Arg::with_name("rgb")
                 .long("rgb")
-                .help("Colors the rasterized characters")
+                .help("Colors the rasterized characters"),
+        )
+        .arg(
+            Arg::with_name("ansi")
+                .long("ansi")
+                .help("Colors the text output with ANSI escape codes")
+                .possible_value("truecolor")
+                .possible_value("256")
+                .possible_value("16")
+                .value_name("ansi"),
+        )
+        .arg(
+            Arg::with_name("ansi background")
+                .long("ansi-background")
+                .requires("ansi")
+                .help("Colors the cell background instead of the character"),
         )
         .get_matches();
 
     let input: PathBuf = matches.value_of("INPUT").unwrap().into();
-    let output: PathBuf = matches.value_of("OUTPUT").unwrap().into();
-    let scale = matches.value_of("scale");
-    let filter = match matches.value_of("filter").unwrap() {
-        "nearest" => FilterType::Nearest,
-        "triangle" => FilterType::Triangle,
-        "catmull-rom" => FilterType::CatmullRom,
-        "gaussian" => FilterType::Gaussian,
-        "lanczos3" => FilterType::Lanczos3,
-        _ => panic!("unsupported filter type"),
+
+    if !is_stdio(&input) && !input.exists() {
+        return Err(Error::NotFound(input));
+    }
+
+    let mut builder = AsciiImage::builder()
+        .filter(parse_filter(matches.value_of("filter").unwrap())?)
+        .dither(Dither::parse(matches.value_of("dither").unwrap())?)
+        .luminance(Luminance::parse(matches.value_of("luminance").unwrap())?)
+        .linear(matches.is_present("linear"))
+        .matching(Matching::parse(matches.value_of("match").unwrap())?)
+        .renderer(Renderer::parse(matches.value_of("renderer").unwrap())?)
+        .glyph_fit(GlyphFit::parse(matches.value_of("glyph fit").unwrap())?)
+        .theme(Theme::parse(matches.value_of("theme").unwrap())?)
+        .transparent(matches.is_present("transparent"))
+        .rgb(matches.is_present("rgb"));
+
+    if let Some(table) = matches.value_of("ascii table") {
+        builder = builder.table(table);
+    }
+
+    if let Some(scale) = matches.value_of("scale") {
+        builder = builder.scale(Scaler::parse(scale)?);
+    }
+
+    if let Some(aspect) = matches.value_of("cell aspect") {
+        builder = builder.cell_aspect(
+            aspect
+                .parse::<f64>()
+                .ok()
+                .filter(|aspect| *aspect > 0.)
+                .ok_or_else(|| Error::InvalidCellAspect(aspect.into()))?,
+        );
+    }
+
+    let mut tone = Tone {
+        equalize: Equalize::parse(matches.value_of("equalize").unwrap())?,
+        invert: matches.is_present("invert"),
+        ..Tone::default()
     };
-    let ascii = matches.value_of("ascii table").unwrap().chars().collect();
-    let rastered = matches.is_present("raster");
-    let font = matches.value_of("font");
-    let font_size = matches
-        .value_of("font size")
-        .unwrap_or("16")
-        .parse::<u32>()
-        .expect("invalid font size");
-
-    if !input.exists() {
-        println!("Can not find input image!");
-        std::process::exit(0);
+
+    if let Equalize::Clahe { tile, clip } = &mut tone.equalize {
+        if let Some(size) = matches.value_of("clahe tile") {
+            *tile = size
+                .parse::<u32>()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| Error::InvalidClaheTile(size.into()))?;
+        }
+
+        if let Some(limit) = matches.value_of("clahe clip") {
+            *clip = limit
+                .parse::<f32>()
+                .ok()
+                .filter(|limit| *limit >= 1.)
+                .ok_or_else(|| Error::InvalidClaheClip(limit.into()))?;
+        }
+    }
+
+    if matches.is_present("auto stretch") {
+        tone.stretch = Some(0.);
+    }
+
+    if let Some(brightness) = matches.value_of("brightness") {
+        tone.brightness = brightness
+            .parse::<f32>()
+            .ok()
+            .filter(|brightness| (-1. ..=1.).contains(brightness))
+            .ok_or_else(|| Error::InvalidBrightness(brightness.into()))?;
+    }
+
+    if let Some(contrast) = matches.value_of("contrast") {
+        tone.contrast = contrast
+            .parse::<f32>()
+            .ok()
+            .filter(|contrast| *contrast >= 0.)
+            .ok_or_else(|| Error::InvalidContrast(contrast.into()))?;
+    }
+
+    if let Some(gamma) = matches.value_of("gamma") {
+        tone.gamma = gamma
+            .parse::<f32>()
+            .ok()
+            .filter(|gamma| *gamma > 0.)
+            .ok_or_else(|| Error::InvalidGamma(gamma.into()))?;
+    }
+
+    if let Some(levels) = matches.value_of("levels") {
+        let (black, white) = levels
+            .split_once(':')
+            .and_then(|(black, white)| Some((black.parse::<u8>().ok()?, white.parse::<u8>().ok()?)))
+            .filter(|(black, white)| black < white)
+            .ok_or_else(|| Error::InvalidLevels(levels.into()))?;
+
+        tone.black = black;
+        tone.white = white;
+    }
+
+    if let Some(clip) = matches.value_of("clip") {
+        tone.stretch = Some(
+            clip.parse::<f32>()
+                .ok()
+                .filter(|clip| (0. ..50.).contains(clip))
+                .ok_or_else(|| Error::InvalidClip(clip.into()))?,
+        );
+    }
+
+    builder = builder.tone(tone);
+
+    let mut alpha = AlphaOptions::default();
+
+    if let Some(background) = matches.value_of("alpha background") {
+        alpha.background = Some(parse_color(background)?);
+    }
+
+    if let Some(threshold) = matches.value_of("alpha threshold") {
+        alpha.threshold = Some(
+            threshold
+                .parse::<u8>()
+                .map_err(|_| Error::InvalidAlphaThreshold(threshold.into()))?,
+        );
+    }
+
+    builder = builder.alpha(alpha);
+
+    if let Some(kernel) = matches.value_of("edges") {
+        let mut edges = EdgeOptions {
+            kernel: EdgeKernel::parse(kernel)?,
+            blank: matches.is_present("edge blank"),
+            ..EdgeOptions::default()
+        };
+
+        if let Some(threshold) = matches.value_of("edge threshold") {
+            edges.threshold = threshold
+                .parse::<f32>()
+                .ok()
+                .filter(|threshold| *threshold >= 0.)
+                .ok_or_else(|| Error::InvalidEdgeThreshold(threshold.into()))?;
+        }
+
+        builder = builder.edges(edges);
+    }
+
+    if let Some(font_path) = matches.value_of("font") {
+        let font_path = PathBuf::from(font_path);
+
+        if !font_path.exists() {
+            return Err(Error::NotFound(font_path));
+        }
+
+        builder = builder.font(fs::read(font_path)?)?;
+    }
+
+    if let Some(levels) = matches.value_of("auto table") {
+        builder = builder.auto_table(
+            matches
+                .value_of("table candidates")
+                .unwrap_or(PRINTABLE_ASCII),
+            levels
+                .parse::<usize>()
+                .map_err(|_| Error::InvalidTableLevels(levels.into()))?,
+        );
+    }
+
+    if let Some(foreground) = matches.value_of("foreground") {
+        builder = builder.foreground(parse_color(foreground)?);
+    }
+
+    if let Some(background) = matches.value_of("background") {
+        builder = builder.background(parse_color(background)?);
+    }
+
+    if let Some(font_size) = matches.value_of("font size") {
+        builder = builder.font_size(
+            font_size
+                .parse::<u32>()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| Error::InvalidFontSize(font_size.into()))?,
+        );
+    }
+
+    if matches.is_present("html") {
+        let mut html = HtmlOptions {
+            background: matches.is_present("html background"),
+            fragment: matches.is_present("html fragment"),
+            ..HtmlOptions::default()
+        };
+
+        if let Some(family) = matches.value_of("html font family") {
+            html.font_family = family.into();
+        }
+
+        if let Some(line_height) = matches.value_of("html line height") {
+            html.line_height = line_height
+                .parse::<f32>()
+                .ok()
+                .filter(|height| *height > 0.)
+                .ok_or_else(|| Error::InvalidLineHeight(line_height.into()))?;
+        }
+
+        builder = builder.html(html);
     }
-    let rgb = matches.is_present("rgb");
 
-    let img = if let Some(size) = scale {
-        let scaler = Scaler::parse(size);
-        let img = image::open(input).expect("failed to open image").to_luma8();
+    builder = builder.output(if matches.is_present("raster") {
+        OutputMode::Raster
+    } else if matches.is_present("html") {
+        OutputMode::Html
+    } else if matches.is_present("svg") {
+        OutputMode::Svg {
+            embed_font: matches.is_present("svg embed font"),
+        }
+    } else if let Some(mode) = matches.value_of("ansi") {
+        OutputMode::Ansi {
+            mode: AnsiMode::parse(mode)?,
+            background: matches.is_present("ansi background"),
+        }
+    } else {
+        OutputMode::Text
+    });
 
-        scaler.scale(&img, filter)
+    let frames = if is_stdio(&input) {
+        let mut data = Vec::new();
+
+        io::stdin().lock().read_to_end(&mut data)?;
+        load_frames(&data)?
     } else {
-        image::open(input).expect("failed to open image").to_luma8()
+        open_frames(&input)?
+    };
+
+    let mut options = PlayOptions {
+        looping: matches.is_present("loop"),
+        ..PlayOptions::default()
     };
 
-    if rastered {
-        let font = if let Some(font_path) = font {
-            if !(PathBuf::from(font_path)).exists() {
-                println!("Can not find font file!");
-                std::process::exit(0);
-            }
-
-            Font::from_bytes(
-                fs::read(font_path).expect("can't read font file"),
-                FontSettings::default(),
-            )
-            .expect("can't parse font file")
-        } else {
-            Font::from_bytes(
-                Assets::get("assets/consolas.ttf").unwrap(),
-                FontSettings::default(),
-            )
-            .unwrap()
+    if let Some(delay) = matches.value_of("frame delay") {
+        options.frame_delay = Duration::from_millis(
+            delay
+                .parse::<u64>()
+                .map_err(|_| Error::InvalidFrameDelay(delay.into()))?,
+        );
+    }
+
+    if matches.is_present("play") {
+        let running = Arc::new(AtomicBool::new(true));
+        let handler = running.clone();
+
+        ctrlc::set_handler(move || handler.store(false, Ordering::SeqCst))?;
+
+        return play(io::stdout().lock(), &builder, &frames, options, &running);
+    }
+
+    let output: PathBuf = matches.value_of("OUTPUT").unwrap().into();
+
+    let format = matches
+        .value_of("format")
+        .map(|format| {
+            ImageFormat::from_extension(format)
+                .ok_or_else(|| Error::UnsupportedFormat(format.into()))
+        })
+        .transpose()?;
+
+    if matches.is_present("cast") {
+        return write_bytes(
+            &output,
+            builder
+                .render_cast(&frames, options.frame_delay)?
+                .as_bytes(),
+        );
+    }
+
+    if let [(image, _)] = &frames[..] {
+        return write_output(&output, builder.render(image)?, format);
+    }
+
+    let outputs = builder.render_frames(&frames)?;
+
+    if matches.is_present("raster") {
+        let is_gif = match format {
+            Some(format) => format == ImageFormat::Gif,
+            None if is_stdio(&output) => return Err(Error::MissingFormat),
+            None => output
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gif")),
         };
 
-        AsciiImage(img, ascii)
-            .rasterize(font, font_size)
-            .save(output)
-            .expect("failed to write output file");
+        if !is_gif {
+            return Err(Error::AnimatedOutput(output));
+        }
+
+        let images = outputs
+            .into_iter()
+            .filter_map(|(output, delay)| match output {
+                Output::Image(img) if delay.is_zero() => Some((img, options.frame_delay)),
+                Output::Image(img) => Some((img, delay)),
+                Output::Text(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let mut bytes = Vec::new();
+
+        write_gif(&mut bytes, &images)?;
+
+        return write_bytes(&output, &bytes);
+    }
+
+    if is_stdio(&output) {
+        let text = outputs
+            .into_iter()
+            .filter_map(|(output, _)| match output {
+                Output::Text(text) => Some(text),
+                Output::Image(_) => None,
+            })
+            .collect::<Vec<String>>()
+            .join("\r\n\r\n");
+
+        return write_bytes(&output, text.as_bytes());
+    }
+
+    let digits = outputs.len().to_string().len();
+
+    for (index, (frame, _)) in outputs.into_iter().enumerate() {
+        write_output(&frame_path(&output, index, digits), frame, format)?;
+    }
+
+    Ok(())
+}
+
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn write_bytes(path: &Path, bytes: &[u8]) -> Result<()> {
+    if is_stdio(path) {
+        io::stdout().lock().write_all(bytes)?;
     } else {
-        let ascii_image = AsciiImage(img, ascii);
-        fs::write(output, ascii_image.to_string()).expect("failed to write output file");
+        fs::write(path, bytes)?;
+    }
+
+    Ok(())
+}
+
+fn write_output(path: &Path, output: Output, format: Option<ImageFormat>) -> Result<()> {
+    match (output, format) {
+        (Output::Text(text), _) => write_bytes(path, text.as_bytes()),
+
+        (Output::Image(img), Some(format)) => {
+            let mut bytes = Vec::new();
+
+            img.write_to(&mut bytes, format)?;
+            write_bytes(path, &bytes)
+        }
+
+        (Output::Image(_), None) if is_stdio(path) => Err(Error::MissingFormat),
+
+        (Output::Image(img), None) => Ok(img.save(path)?),
+    }
+}
+
+fn frame_path(output: &Path, index: usize, digits: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!(
+            "{}-{:0digits$}.{}",
+            stem,
+            index,
+            ext.to_string_lossy(),
+            digits = digits
+        ),
+        None => format!("{}-{:0digits$}", stem, index, digits = digits),
+    };
+
+    output.with_file_name(name)
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
 }
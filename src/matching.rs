@@ -0,0 +1,188 @@
+use crate::{
+    raster::{raster_cache, RasterCache, RasterOptions},
+    Error, Result,
+};
+use fontdue::Font;
+use image::{
+    imageops::{resize, FilterType},
+    GrayImage,
+};
+
+pub const SUB_CELL_WIDTH: u32 = 4;
+pub const SUB_CELL_HEIGHT: u32 = 8;
+
+const C1: f32 = (0.01 * 255.) * (0.01 * 255.);
+const C2: f32 = (0.03 * 255.) * (0.03 * 255.);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Matching {
+    #[default]
+    Luminance,
+    Sse,
+    Ssim,
+}
+
+impl Matching {
+    pub fn parse(matching: &str) -> Result<Matching> {
+        match matching {
+            "luminance" => Ok(Matching::Luminance),
+            "sse" => Ok(Matching::Sse),
+            "ssim" => Ok(Matching::Ssim),
+            _ => Err(Error::UnsupportedMatching(matching.into())),
+        }
+    }
+
+    pub(crate) fn match_cells(
+        &self,
+        samples: &GrayImage,
+        table: &[char],
+        font: &Font,
+        options: &RasterOptions,
+    ) -> Result<Vec<char>> {
+        if table.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+
+        let (cache, _) = raster_cache(font, table.iter().copied(), options);
+        let templates = templates(&cache, table);
+        let (width, height) = (
+            samples.width() / SUB_CELL_WIDTH,
+            samples.height() / SUB_CELL_HEIGHT,
+        );
+
+        let mut cell = Vec::with_capacity((SUB_CELL_WIDTH * SUB_CELL_HEIGHT) as usize);
+        let mut chars = Vec::with_capacity((width * height) as usize);
+
+        for iy in 0..height {
+            for ix in 0..width {
+                cell.clear();
+
+                for sy in 0..SUB_CELL_HEIGHT {
+                    for sx in 0..SUB_CELL_WIDTH {
+                        let x = ix * SUB_CELL_WIDTH + sx;
+                        let y = iy * SUB_CELL_HEIGHT + sy;
+
+                        cell.push(samples.get_pixel(x, y).0[0] as f32);
+                    }
+                }
+
+                chars.push(self.best_match(&cell, &templates).unwrap_or(table[0]));
+            }
+        }
+
+        Ok(chars)
+    }
+
+    fn best_match(&self, cell: &[f32], templates: &[(char, Vec<f32>)]) -> Option<char> {
+        let flat = variance(cell) < C2;
+
+        templates
+            .iter()
+            .map(|(c, template)| {
+                let score = match self {
+                    Matching::Ssim if !flat => -ssim(cell, template),
+                    _ => sse(cell, template),
+                };
+
+                (*c, score)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(c, _)| c)
+    }
+}
+
+fn templates(cache: &RasterCache, table: &[char]) -> Vec<(char, Vec<f32>)> {
+    table
+        .iter()
+        .map(|c| {
+            let template = resize(
+                &cache[c],
+                SUB_CELL_WIDTH,
+                SUB_CELL_HEIGHT,
+                FilterType::Triangle,
+            );
+
+            (*c, template.pixels().map(|p| p.0[0] as f32).collect())
+        })
+        .collect()
+}
+
+fn sse(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+fn variance(a: &[f32]) -> f32 {
+    let n = a.len() as f32;
+    let mean = a.iter().sum::<f32>() / n;
+
+    a.iter().map(|a| (a - mean) * (a - mean)).sum::<f32>() / n
+}
+
+fn ssim(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() as f32;
+    let mean_a = a.iter().sum::<f32>() / n;
+    let mean_b = b.iter().sum::<f32>() / n;
+
+    let (mut var_a, mut var_b, mut covariance) = (0., 0., 0.);
+
+    for (a, b) in a.iter().zip(b) {
+        var_a += (a - mean_a) * (a - mean_a);
+        var_b += (b - mean_b) * (b - mean_b);
+        covariance += (a - mean_a) * (b - mean_b);
+    }
+
+    let (var_a, var_b, covariance) = (var_a / n, var_b / n, covariance / n);
+
+    (2. * mean_a * mean_b + C1) * (2. * covariance + C2)
+        / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> Vec<(char, Vec<f32>)> {
+        let cells = (SUB_CELL_WIDTH * SUB_CELL_HEIGHT) as usize;
+        let pattern = |every: usize| {
+            (0..cells)
+                .map(|i| if i % every == 0 { 255. } else { 0. })
+                .collect()
+        };
+        let dots = |every: usize| {
+            (0..cells)
+                .map(|i| if i % every == 0 { 0. } else { 255. })
+                .collect()
+        };
+
+        vec![
+            ('@', pattern(4)),
+            ('+', pattern(2)),
+            ('.', dots(8)),
+            (' ', vec![255.; cells]),
+        ]
+    }
+
+    #[test]
+    fn flat_cells_follow_brightness() {
+        let cells = (SUB_CELL_WIDTH * SUB_CELL_HEIGHT) as usize;
+
+        for matching in [Matching::Sse, Matching::Ssim] {
+            assert_eq!(
+                matching.best_match(&vec![0.; cells], &templates()),
+                Some('@')
+            );
+            assert_eq!(
+                matching.best_match(&vec![255.; cells], &templates()),
+                Some(' ')
+            );
+        }
+    }
+
+    #[test]
+    fn ssim_prefers_matching_structure() {
+        let (_, dots) = &templates()[2];
+
+        assert_eq!(Matching::Ssim.best_match(dots, &templates()), Some('.'));
+        assert!((ssim(dots, dots) - 1.).abs() < 1e-6);
+    }
+}
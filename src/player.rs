@@ -0,0 +1,107 @@
+use crate::{AsciiImageBuilder, Output, Result};
+use image::DynamicImage;
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlayOptions {
+    pub looping: bool,
+    pub frame_delay: Duration,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        PlayOptions {
+            looping: false,
+            frame_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+pub fn play<W: Write>(
+    mut out: W,
+    builder: &AsciiImageBuilder,
+    frames: &[(DynamicImage, Duration)],
+    options: PlayOptions,
+    running: &AtomicBool,
+) -> Result<()> {
+    write!(out, "\x1b[?25l\x1b[2J")?;
+
+    let result = play_frames(&mut out, builder, frames, options, running);
+
+    write!(out, "\x1b[0m\x1b[?25h\r\n")?;
+    out.flush()?;
+
+    result
+}
+
+fn play_frames<W: Write>(
+    out: &mut W,
+    builder: &AsciiImageBuilder,
+    frames: &[(DynamicImage, Duration)],
+    options: PlayOptions,
+    running: &AtomicBool,
+) -> Result<()> {
+    let delays: Vec<Duration> = frames
+        .iter()
+        .map(|(_, delay)| {
+            if delay.is_zero() {
+                options.frame_delay
+            } else {
+                *delay
+            }
+        })
+        .collect();
+    let total: Duration = delays.iter().sum();
+
+    if frames.is_empty() || total.is_zero() {
+        return Ok(());
+    }
+
+    let mut rendered: Vec<Option<String>> = vec![None; frames.len()];
+    let start = Instant::now();
+
+    while running.load(Ordering::SeqCst) {
+        let elapsed = start.elapsed();
+
+        if !options.looping && elapsed >= total {
+            break;
+        }
+
+        let mut time = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+        let mut index = 0;
+
+        while time >= delays[index] {
+            time -= delays[index];
+            index += 1;
+        }
+
+        if rendered[index].is_none() {
+            rendered[index] = Some(match builder.render(&frames[index].0)? {
+                Output::Text(text) => text,
+                Output::Image(_) => String::new(),
+            });
+        }
+
+        write!(
+            out,
+            "\x1b[H{}\x1b[0J",
+            rendered[index].as_deref().unwrap_or("")
+        )?;
+        out.flush()?;
+
+        let deadline = start + (elapsed - time + delays[index]);
+
+        while running.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use crate::{renderer::block_glyph, Error, Result};
+use fontdue::Font;
+use image::{GrayImage, ImageBuffer, Luma, Rgb};
+use std::collections::{HashMap, HashSet};
+
+pub(crate) type RasterCache = HashMap<char, GrayImage>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GlyphFit {
+    #[default]
+    Clip,
+    Shrink,
+    Widen,
+}
+
+impl GlyphFit {
+    pub fn parse(fit: &str) -> Result<GlyphFit> {
+        match fit {
+            "clip" => Ok(GlyphFit::Clip),
+            "shrink" => Ok(GlyphFit::Shrink),
+            "widen" => Ok(GlyphFit::Widen),
+            _ => Err(Error::UnsupportedGlyphFit(fit.into())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Terminal,
+}
+
+impl Theme {
+    pub fn parse(theme: &str) -> Result<Theme> {
+        match theme {
+            "light" => Ok(Theme::Light),
+            "terminal" => Ok(Theme::Terminal),
+            _ => Err(Error::UnsupportedTheme(theme.into())),
+        }
+    }
+
+    pub fn colors(&self) -> (Rgb<u8>, Rgb<u8>) {
+        match self {
+            Theme::Light => (Rgb([0; 3]), Rgb([255; 3])),
+            Theme::Terminal => (Rgb([204; 3]), Rgb([0; 3])),
+        }
+    }
+}
+
+pub fn parse_color(color: &str) -> Result<Rgb<u8>> {
+    let invalid = || Error::InvalidColor(color.into());
+    let hex = color.trim_start_matches('#');
+
+    let channels = if color.contains(',') {
+        color
+            .split(',')
+            .map(|c| c.trim().parse::<u8>().map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>>>()?
+    } else if hex.len() == 6 && hex.is_ascii() {
+        (0..3)
+            .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>>>()?
+    } else if hex.len() == 3 && hex.is_ascii() {
+        (0..3)
+            .map(|i| {
+                u8::from_str_radix(&hex[i..i + 1], 16)
+                    .map(|c| c * 17)
+                    .map_err(|_| invalid())
+            })
+            .collect::<Result<Vec<u8>>>()?
+    } else {
+        return Err(invalid());
+    };
+
+    match channels[..] {
+        [r, g, b] => Ok(Rgb([r, g, b])),
+        _ => Err(invalid()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RasterOptions {
+    pub font_size: u32,
+    pub fit: GlyphFit,
+    pub foreground: Rgb<u8>,
+    pub background: Rgb<u8>,
+    pub transparent: bool,
+    pub rgb: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        let (foreground, background) = Theme::Light.colors();
+
+        RasterOptions {
+            font_size: 16,
+            fit: GlyphFit::Clip,
+            foreground,
+            background,
+            transparent: false,
+            rgb: false,
+        }
+    }
+}
+
+struct Layout {
+    glyphs: Vec<(char, Glyph)>,
+    cell: (u32, u32),
+    origin: (i32, i32),
+}
+
+struct Glyph {
+    left: i32,
+    top: i32,
+    width: usize,
+    height: usize,
+    bitmap: Vec<u8>,
+}
+
+pub fn cell_aspect(font: &Font) -> f64 {
+    const PX: f32 = 64.;
+
+    let advance_width = font.metrics('M', PX).advance_width;
+    let line_height = font
+        .horizontal_line_metrics(PX)
+        .map(|line| line.new_line_size)
+        .unwrap_or(PX);
+
+    if advance_width > 0. {
+        (line_height / advance_width) as f64
+    } else {
+        1.
+    }
+}
+
+pub fn cell_size(font: &Font, px: u32) -> (u32, u32) {
+    let advance_width = font.metrics('M', px as f32).advance_width;
+    let line_height = font
+        .horizontal_line_metrics(px as f32)
+        .map(|line| line.new_line_size)
+        .unwrap_or(px as f32);
+
+    (
+        (advance_width.ceil() as u32).max(1),
+        (line_height.ceil() as u32).max(1),
+    )
+}
+
+pub(crate) fn baseline(font: &Font, px: u32) -> i32 {
+    font.horizontal_line_metrics(px as f32)
+        .map(|line| line.ascent.round() as i32)
+        .unwrap_or(px as i32 * 4 / 5)
+}
+
+fn place(font: &Font, c: char, px: f32, baseline: i32) -> Glyph {
+    let (metrics, bitmap) = font.rasterize(c, px);
+
+    Glyph {
+        left: metrics.xmin,
+        top: baseline - metrics.ymin - metrics.height as i32,
+        width: metrics.width,
+        height: metrics.height,
+        bitmap,
+    }
+}
+
+fn shrink(font: &Font, c: char, px: u32, baseline: i32, (width, height): (u32, u32)) -> Glyph {
+    let mut size = px as f32;
+    let mut glyph = place(font, c, size, baseline);
+
+    while (glyph.width > width as usize || glyph.height > height as usize) && size > 1. {
+        let factor = (width as f32 / glyph.width as f32)
+            .min(height as f32 / glyph.height as f32)
+            .min(1.);
+
+        size = (size * factor).floor().min(size - 1.).max(1.);
+        glyph = place(font, c, size, baseline);
+    }
+
+    glyph.left = glyph.left.min(width as i32 - glyph.width as i32).max(0);
+    glyph.top = glyph.top.min(height as i32 - glyph.height as i32).max(0);
+
+    glyph
+}
+
+pub(crate) fn raster_cache(
+    font: &Font,
+    chars: impl Iterator<Item = char>,
+    options: &RasterOptions,
+) -> (RasterCache, (u32, u32)) {
+    let chars: HashSet<char> = chars.collect();
+    let Layout {
+        glyphs,
+        cell: (width, height),
+        ..
+    } = layout(font, &chars, options);
+
+    let mut cache: RasterCache = chars
+        .iter()
+        .filter_map(|c| {
+            block_glyph(*c).map(|(renderer, bits)| (*c, renderer.bitmap(bits, width, height)))
+        })
+        .collect();
+
+    for (c, glyph) in glyphs {
+        let mut img: GrayImage = ImageBuffer::from_pixel(width, height, Luma([255]));
+
+        for (i, coverage) in glyph.bitmap.into_iter().enumerate() {
+            let x = glyph.left + (i % glyph.width) as i32;
+            let y = glyph.top + (i / glyph.width) as i32;
+
+            if x >= 0 && y >= 0 && x < width as i32 && y < height as i32 {
+                img.put_pixel(x as u32, y as u32, Luma([255 - coverage]));
+            }
+        }
+
+        cache.insert(c, img);
+    }
+
+    (cache, (width, height))
+}
+
+pub(crate) fn text_layout(
+    font: &Font,
+    chars: impl Iterator<Item = char>,
+    options: &RasterOptions,
+) -> ((u32, u32), (i32, i32)) {
+    let layout = layout(font, &chars.collect(), options);
+
+    (layout.cell, layout.origin)
+}
+
+fn layout(font: &Font, chars: &HashSet<char>, options: &RasterOptions) -> Layout {
+    let px = options.font_size;
+    let baseline = baseline(font, px);
+    let (mut width, mut height) = cell_size(font, px);
+    let mut origin = (0, baseline);
+
+    let mut glyphs: Vec<(char, Glyph)> = chars
+        .iter()
+        .filter(|c| block_glyph(**c).is_none())
+        .map(|c| match options.fit {
+            GlyphFit::Shrink => (*c, shrink(font, *c, px, baseline, (width, height))),
+            _ => (*c, place(font, *c, px as f32, baseline)),
+        })
+        .collect();
+
+    if options.fit == GlyphFit::Widen {
+        let left = glyphs.iter().map(|(_, g)| g.left).fold(0, i32::min);
+        let top = glyphs.iter().map(|(_, g)| g.top).fold(0, i32::min);
+        let right = glyphs
+            .iter()
+            .map(|(_, g)| g.left + g.width as i32)
+            .fold(width as i32, i32::max);
+        let bottom = glyphs
+            .iter()
+            .map(|(_, g)| g.top + g.height as i32)
+            .fold(height as i32, i32::max);
+
+        for (_, glyph) in glyphs.iter_mut() {
+            glyph.left -= left;
+            glyph.top -= top;
+        }
+
+        width = (right - left) as u32;
+        height = (bottom - top) as u32;
+        origin = (-left, baseline - top);
+    }
+
+    Layout {
+        glyphs,
+        cell: (width, height),
+        origin,
+    }
+}
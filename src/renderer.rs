@@ -0,0 +1,276 @@
+use crate::{Error, Result};
+use image::{GrayImage, ImageBuffer, Luma, Pixel, Rgb, RgbImage};
+
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+const BRAILLE_DOTS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Renderer {
+    #[default]
+    Ramp,
+    HalfBlock,
+    Quadrant,
+    Sextant,
+    Braille,
+}
+
+impl Renderer {
+    pub fn parse(renderer: &str) -> Result<Renderer> {
+        match renderer {
+            "ramp" => Ok(Renderer::Ramp),
+            "half-block" => Ok(Renderer::HalfBlock),
+            "quadrant" => Ok(Renderer::Quadrant),
+            "sextant" => Ok(Renderer::Sextant),
+            "braille" => Ok(Renderer::Braille),
+            _ => Err(Error::UnsupportedRenderer(renderer.into())),
+        }
+    }
+
+    pub fn sub_cells(&self) -> (u32, u32) {
+        match self {
+            Renderer::Ramp => (1, 1),
+            Renderer::HalfBlock => (1, 2),
+            Renderer::Quadrant => (2, 2),
+            Renderer::Sextant => (2, 3),
+            Renderer::Braille => (2, 4),
+        }
+    }
+
+    pub fn glyph(&self, bits: u32) -> char {
+        match self {
+            Renderer::Ramp => ' ',
+            Renderer::HalfBlock => [' ', '▀', '▄', '█'][bits as usize & 3],
+            Renderer::Quadrant => QUADRANTS[bits as usize & 15],
+            Renderer::Sextant => match bits & 63 {
+                0 => ' ',
+                21 => '▌',
+                42 => '▐',
+                63 => '█',
+                bits => {
+                    let skipped = (bits > 21) as u32 + (bits > 42) as u32;
+
+                    char::from_u32(0x1fb00 + bits - 1 - skipped).unwrap_or('█')
+                }
+            },
+            Renderer::Braille => {
+                let dots = (0..8)
+                    .filter(|i| bits & (1 << i) != 0)
+                    .map(|i| BRAILLE_DOTS[i])
+                    .sum::<u32>();
+
+                char::from_u32(0x2800 + dots).unwrap_or(' ')
+            }
+        }
+    }
+
+    pub(crate) fn render(
+        &self,
+        (width, height): (u32, u32),
+        ink: Option<&[bool]>,
+        colors: &RgbImage,
+    ) -> (Vec<char>, Vec<Rgb<u8>>, Vec<Rgb<u8>>) {
+        let (cols, rows) = self.sub_cells();
+        let stride = width * cols;
+
+        let mut chars = Vec::with_capacity((width * height) as usize);
+        let mut fg = Vec::with_capacity(chars.capacity());
+        let mut bg = Vec::with_capacity(chars.capacity());
+
+        for iy in 0..height {
+            for ix in 0..width {
+                let positions: Vec<(u32, u32)> = (0..rows)
+                    .flat_map(|sy| (0..cols).map(move |sx| (ix * cols + sx, iy * rows + sy)))
+                    .collect();
+                let pixels: Vec<Rgb<u8>> = positions
+                    .iter()
+                    .map(|(x, y)| *colors.get_pixel(*x, *y))
+                    .collect();
+
+                let bits = match ink {
+                    Some(ink) => positions
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (x, y))| ink[(y * stride + x) as usize])
+                        .map(|(i, _)| 1 << i)
+                        .sum(),
+                    None if *self == Renderer::HalfBlock => 1,
+                    None => split(&pixels),
+                };
+
+                let average = |is_ink: bool| {
+                    let mut sum = [0u32; 4];
+
+                    for (i, pixel) in pixels.iter().enumerate() {
+                        if (bits & (1 << i) != 0) == is_ink {
+                            for (s, c) in sum.iter_mut().zip(pixel.0.iter()) {
+                                *s += *c as u32;
+                            }
+
+                            sum[3] += 1;
+                        }
+                    }
+
+                    Some(sum)
+                        .filter(|sum| sum[3] > 0)
+                        .map(|sum| Rgb([0, 1, 2].map(|i| (sum[i] / sum[3]) as u8)))
+                };
+                let (paper, ink) = match (average(false), average(true)) {
+                    (Some(paper), Some(ink)) => (paper, ink),
+                    (Some(color), None) | (None, Some(color)) => (color, color),
+                    (None, None) => (Rgb([0; 3]), Rgb([0; 3])),
+                };
+
+                chars.push(self.glyph(bits));
+                fg.push(ink);
+                bg.push(paper);
+            }
+        }
+
+        (chars, fg, bg)
+    }
+
+    pub(crate) fn bitmap(&self, bits: u32, width: u32, height: u32) -> GrayImage {
+        let (cols, rows) = self.sub_cells();
+
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let (col, row) = (x * cols / width, y * rows / height);
+
+            let ink = bits & (1 << (row * cols + col)) != 0
+                && (*self != Renderer::Braille || {
+                    let (w, h) = (width as f32 / cols as f32, height as f32 / rows as f32);
+                    let dx = x as f32 + 0.5 - (col as f32 + 0.5) * w;
+                    let dy = y as f32 + 0.5 - (row as f32 + 0.5) * h;
+                    let radius = w.min(h) * 0.35;
+
+                    dx * dx + dy * dy <= radius * radius
+                });
+
+            Luma([if ink { 0 } else { 255 }])
+        })
+    }
+}
+
+fn split(pixels: &[Rgb<u8>]) -> u32 {
+    let distance = |a: &Rgb<u8>, b: &Rgb<u8>| {
+        (0..3)
+            .map(|i| (a.0[i] as i32 - b.0[i] as i32).pow(2))
+            .sum::<i32>()
+    };
+
+    let (a, b) = (0..pixels.len())
+        .flat_map(|a| (a + 1..pixels.len()).map(move |b| (a, b)))
+        .max_by_key(|(a, b)| distance(&pixels[*a], &pixels[*b]))
+        .unwrap_or((0, 0));
+    let (ink, paper) = if pixels[a].to_luma().0[0] <= pixels[b].to_luma().0[0] {
+        (pixels[a], pixels[b])
+    } else {
+        (pixels[b], pixels[a])
+    };
+
+    pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, pixel)| distance(pixel, &ink) < distance(pixel, &paper))
+        .map(|(i, _)| 1 << i)
+        .sum()
+}
+
+pub(crate) fn block_glyph(c: char) -> Option<(Renderer, u32)> {
+    [
+        Renderer::HalfBlock,
+        Renderer::Quadrant,
+        Renderer::Sextant,
+        Renderer::Braille,
+    ]
+    .iter()
+    .find_map(|renderer| {
+        let (cols, rows) = renderer.sub_cells();
+
+        (0..1 << (cols * rows))
+            .find(|bits| renderer.glyph(*bits) == c)
+            .map(|bits| (*renderer, bits))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_block_glyphs() {
+        let glyphs: Vec<char> = (0..4).map(|bits| Renderer::HalfBlock.glyph(bits)).collect();
+
+        assert_eq!(glyphs, [' ', '▀', '▄', '█']);
+    }
+
+    #[test]
+    fn quadrant_glyphs() {
+        assert_eq!(Renderer::Quadrant.glyph(0b0001), '▘');
+        assert_eq!(Renderer::Quadrant.glyph(0b0010), '▝');
+        assert_eq!(Renderer::Quadrant.glyph(0b0100), '▖');
+        assert_eq!(Renderer::Quadrant.glyph(0b1000), '▗');
+        assert_eq!(Renderer::Quadrant.glyph(0b0110), '▞');
+        assert_eq!(Renderer::Quadrant.glyph(0b1111), '█');
+    }
+
+    #[test]
+    fn sextant_glyphs() {
+        assert_eq!(Renderer::Sextant.glyph(0), ' ');
+        assert_eq!(Renderer::Sextant.glyph(1), '\u{1fb00}');
+        assert_eq!(Renderer::Sextant.glyph(20), '\u{1fb13}');
+        assert_eq!(Renderer::Sextant.glyph(21), '▌');
+        assert_eq!(Renderer::Sextant.glyph(22), '\u{1fb14}');
+        assert_eq!(Renderer::Sextant.glyph(41), '\u{1fb27}');
+        assert_eq!(Renderer::Sextant.glyph(42), '▐');
+        assert_eq!(Renderer::Sextant.glyph(43), '\u{1fb28}');
+        assert_eq!(Renderer::Sextant.glyph(62), '\u{1fb3b}');
+        assert_eq!(Renderer::Sextant.glyph(63), '█');
+    }
+
+    #[test]
+    fn block_glyphs_are_distinct() {
+        for renderer in [
+            Renderer::HalfBlock,
+            Renderer::Quadrant,
+            Renderer::Sextant,
+            Renderer::Braille,
+        ] {
+            let (cols, rows) = renderer.sub_cells();
+            let glyphs: std::collections::HashSet<char> = (0..1 << (cols * rows))
+                .map(|bits| renderer.glyph(bits))
+                .collect();
+
+            assert_eq!(glyphs.len(), 1 << (cols * rows));
+        }
+    }
+
+    #[test]
+    fn braille_dot_order() {
+        assert_eq!(Renderer::Braille.glyph(0), '\u{2800}');
+        assert_eq!(Renderer::Braille.glyph(1 << 0), '⠁');
+        assert_eq!(Renderer::Braille.glyph(1 << 1), '⠈');
+        assert_eq!(Renderer::Braille.glyph(1 << 2), '⠂');
+        assert_eq!(Renderer::Braille.glyph(1 << 3), '⠐');
+        assert_eq!(Renderer::Braille.glyph(1 << 4), '⠄');
+        assert_eq!(Renderer::Braille.glyph(1 << 5), '⠠');
+        assert_eq!(Renderer::Braille.glyph(1 << 6), '⡀');
+        assert_eq!(Renderer::Braille.glyph(1 << 7), '⢀');
+        assert_eq!(Renderer::Braille.glyph(0b0101_0101), '⡇');
+        assert_eq!(Renderer::Braille.glyph(255), '⣿');
+    }
+
+    #[test]
+    fn colored_half_block_keeps_both_colors() {
+        let colors = RgbImage::from_fn(1, 2, |_, y| {
+            Rgb(if y == 0 { [255, 255, 0] } else { [0, 255, 255] })
+        });
+        let (chars, fg, bg) = Renderer::HalfBlock.render((1, 1), None, &colors);
+
+        assert_eq!(chars, ['▀']);
+        assert_eq!(fg, [Rgb([255, 255, 0])]);
+        assert_eq!(bg, [Rgb([0, 255, 255])]);
+    }
+}
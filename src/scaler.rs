@@ -0,0 +1,200 @@
+use crate::{Error, Result};
+use image::{
+    imageops::{resize, FilterType},
+    GenericImageView, ImageBuffer, Pixel,
+};
+
+pub fn parse_filter(filter: &str) -> Result<FilterType> {
+    match filter {
+        "nearest" => Ok(FilterType::Nearest),
+        "triangle" => Ok(FilterType::Triangle),
+        "catmull-rom" => Ok(FilterType::CatmullRom),
+        "gaussian" => Ok(FilterType::Gaussian),
+        "lanczos3" => Ok(FilterType::Lanczos3),
+        _ => Err(Error::UnsupportedFilter(filter.into())),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Scaler {
+    Size(Option<u32>, Option<u32>),
+    Fit(u32, u32),
+}
+
+impl Default for Scaler {
+    fn default() -> Self {
+        Scaler::Size(None, None)
+    }
+}
+
+impl Scaler {
+    pub fn parse(scale: &str) -> Result<Scaler> {
+        if scale == "fit" {
+            let (columns, rows) = terminal_size().ok_or(Error::NoTerminal)?;
+
+            return Ok(Scaler::Fit(columns, rows.saturating_sub(1).max(1)));
+        }
+
+        let sizes = scale
+            .split(':')
+            .map(|s| match s {
+                "_" => Ok(None),
+                num => num
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .map(Some)
+                    .ok_or_else(|| Error::InvalidScale(scale.into())),
+            })
+            .collect::<Result<Vec<Option<u32>>>>()?;
+
+        match sizes[..] {
+            [size] => Ok(Scaler::Size(size, size)),
+            [width, height] => Ok(Scaler::Size(width, height)),
+            _ => Err(Error::InvalidScale(scale.into())),
+        }
+    }
+
+    pub fn dimensions(&self, (width, height): (u32, u32), aspect: f64) -> (u32, u32) {
+        let (target_width, target_height) = match *self {
+            Scaler::Size(width, height) => (width, height),
+
+            Scaler::Fit(columns, rows) => {
+                let ratio = width as f64 / height as f64 * aspect;
+                let width = (columns as f64).min(rows as f64 * ratio);
+
+                return ((width as u32).max(1), ((width / ratio) as u32).max(1));
+            }
+        };
+
+        match (target_width, target_height) {
+            (Some(width), Some(height)) => (width, ((height as f64 / aspect) as u32).max(1)),
+
+            (Some(target_width), None) => {
+                let target_height = target_width as f64 / width as f64 * height as f64 / aspect;
+
+                (target_width, (target_height as u32).max(1))
+            }
+
+            (None, Some(target_height)) => {
+                let target_width = target_height as f64 / height as f64 * width as f64 * aspect;
+
+                ((target_width as u32).max(1), target_height)
+            }
+
+            (None, None) => (width, ((height as f64 / aspect) as u32).max(1)),
+        }
+    }
+
+    pub fn scale<I: GenericImageView>(
+        &self,
+        image: &I,
+        filter: FilterType,
+        aspect: f64,
+    ) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+    where
+        I::Pixel: 'static,
+        <I::Pixel as Pixel>::Subpixel: 'static,
+    {
+        resize_to(image, self.dimensions(image.dimensions(), aspect), filter)
+    }
+}
+
+pub(crate) fn resize_to<I: GenericImageView>(
+    image: &I,
+    (width, height): (u32, u32),
+    filter: FilterType,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+{
+    if image.dimensions() != (width, height) {
+        return resize(image, width, height, filter);
+    }
+
+    ImageBuffer::from_raw(
+        width,
+        height,
+        image
+            .pixels()
+            .flat_map(|(_, _, pixel)| pixel.channels().to_vec())
+            .collect(),
+    )
+    .expect("image buffer matches the source dimensions")
+}
+
+#[cfg(unix)]
+fn terminal_size() -> Option<(u32, u32)> {
+    [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO]
+        .iter()
+        .find_map(|fd| {
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+            match unsafe { libc::ioctl(*fd, libc::TIOCGWINSZ, &mut size) } {
+                0 if size.ws_col > 0 && size.ws_row > 0 => {
+                    Some((size.ws_col as u32, size.ws_row as u32))
+                }
+                _ => None,
+            }
+        })
+}
+
+#[cfg(not(unix))]
+fn terminal_size() -> Option<(u32, u32)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_applies_the_aspect() {
+        assert_eq!(
+            Scaler::Size(None, None).dimensions((100, 50), 2.),
+            (100, 25)
+        );
+        assert_eq!(
+            Scaler::Size(Some(40), None).dimensions((100, 50), 2.),
+            (40, 10)
+        );
+        assert_eq!(
+            Scaler::Size(None, Some(10)).dimensions((100, 50), 2.),
+            (40, 10)
+        );
+        assert_eq!(
+            Scaler::Size(Some(40), Some(40)).dimensions((100, 50), 2.),
+            (40, 20)
+        );
+        assert_eq!(Scaler::Size(Some(1), None).dimensions((100, 1), 2.), (1, 1));
+    }
+
+    #[test]
+    fn fit_stays_within_the_terminal() {
+        assert_eq!(Scaler::Fit(80, 24).dimensions((100, 50), 2.), (80, 20));
+        assert_eq!(Scaler::Fit(80, 10).dimensions((100, 50), 2.), (40, 10));
+        assert_eq!(Scaler::Fit(80, 24).dimensions((1, 1000), 2.), (1, 24));
+    }
+
+    #[test]
+    fn parse_sizes() {
+        assert!(matches!(
+            Scaler::parse("40"),
+            Ok(Scaler::Size(Some(40), Some(40)))
+        ));
+        assert!(matches!(
+            Scaler::parse("40:_"),
+            Ok(Scaler::Size(Some(40), None))
+        ));
+        assert!(matches!(
+            Scaler::parse("_:20"),
+            Ok(Scaler::Size(None, Some(20)))
+        ));
+        assert!(matches!(Scaler::parse("40:x"), Err(Error::InvalidScale(_))));
+        assert!(matches!(
+            Scaler::parse("1:2:3"),
+            Err(Error::InvalidScale(_))
+        ));
+    }
+}
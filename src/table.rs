@@ -0,0 +1,65 @@
+use fontdue::Font;
+use std::collections::HashSet;
+
+const COVERAGE_PX: f32 = 48.;
+
+pub const PRINTABLE_ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+pub fn coverage(font: &Font, c: char) -> f32 {
+    let (metrics, bitmap) = font.rasterize(c, COVERAGE_PX);
+
+    let line_height = font
+        .horizontal_line_metrics(COVERAGE_PX)
+        .map(|line| line.new_line_size)
+        .unwrap_or(COVERAGE_PX);
+    let advance_width = if metrics.advance_width > 0. {
+        metrics.advance_width
+    } else {
+        COVERAGE_PX
+    };
+
+    bitmap.iter().map(|p| *p as f32 / 255.).sum::<f32>() / (advance_width * line_height)
+}
+
+pub fn generate_table(font: &Font, candidates: &[char], levels: usize) -> Vec<char> {
+    let mut seen = HashSet::new();
+    let mut glyphs: Vec<(char, f32)> = candidates
+        .iter()
+        .filter(|c| seen.insert(**c))
+        .filter(|c| c.is_whitespace() || font.lookup_glyph_index(**c) != 0)
+        .map(|c| (*c, coverage(font, *c)))
+        .collect();
+
+    glyphs.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    if glyphs.is_empty() || levels == 0 {
+        return Vec::new();
+    }
+
+    if levels == 1 {
+        return vec![glyphs[0].0];
+    }
+
+    let darkest = glyphs[0].1;
+    let lightest = glyphs[glyphs.len() - 1].1;
+    let mut used = vec![false; glyphs.len()];
+    let mut table = Vec::new();
+
+    for level in 0..levels {
+        let target = darkest - (darkest - lightest) * level as f32 / (levels - 1) as f32;
+
+        let nearest = (0..glyphs.len()).filter(|i| !used[*i]).min_by(|a, b| {
+            (glyphs[*a].1 - target)
+                .abs()
+                .total_cmp(&(glyphs[*b].1 - target).abs())
+        });
+
+        if let Some(i) = nearest {
+            used[i] = true;
+            table.push(i);
+        }
+    }
+
+    table.sort_unstable();
+    table.into_iter().map(|i| glyphs[i].0).collect()
+}
@@ -0,0 +1,360 @@
+use crate::{Error, Result};
+use image::{GrayImage, ImageBuffer, Luma, Rgb};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Equalize {
+    #[default]
+    None,
+    Global,
+    Clahe {
+        tile: u32,
+        clip: f32,
+    },
+}
+
+impl Equalize {
+    pub fn parse(equalize: &str) -> Result<Equalize> {
+        match equalize {
+            "none" => Ok(Equalize::None),
+            "global" => Ok(Equalize::Global),
+            "clahe" => Ok(Equalize::Clahe { tile: 8, clip: 2. }),
+            _ => Err(Error::UnsupportedEqualize(equalize.into())),
+        }
+    }
+
+    fn mapping(&self, image: &GrayImage) -> Mapping {
+        match *self {
+            Equalize::None => Mapping::Identity,
+            Equalize::Global => equalize(image),
+            Equalize::Clahe { tile, clip } => clahe(image, tile.max(1), clip),
+        }
+    }
+}
+
+enum Mapping {
+    Identity,
+    Global(Box<[u8; 256]>),
+    Tiles {
+        tile: u32,
+        columns: u32,
+        rows: u32,
+        tables: Vec<[u8; 256]>,
+    },
+}
+
+impl Mapping {
+    fn get(&self, x: u32, y: u32, value: u8) -> u8 {
+        let (tile, columns, rows, tables) = match self {
+            Mapping::Identity => return value,
+            Mapping::Global(table) => return table[value as usize],
+            Mapping::Tiles {
+                tile,
+                columns,
+                rows,
+                tables,
+            } => (*tile, *columns, *rows, tables),
+        };
+
+        let neighbours = |position: u32, tiles: u32| {
+            let grid = ((position as f32 + 0.5) / tile as f32 - 0.5).max(0.);
+            let first = (grid as u32).min(tiles - 1);
+
+            (first, (first + 1).min(tiles - 1), grid - first as f32)
+        };
+
+        let (x0, x1, fx) = neighbours(x, columns);
+        let (y0, y1, fy) = neighbours(y, rows);
+        let value = |tx: u32, ty: u32| tables[(ty * columns + tx) as usize][value as usize] as f32;
+
+        let top = value(x0, y0) * (1. - fx) + value(x1, y0) * fx;
+        let bottom = value(x0, y1) * (1. - fx) + value(x1, y1) * fx;
+
+        (top * (1. - fy) + bottom * fy).round() as u8
+    }
+
+    fn apply(&self, image: &GrayImage) -> GrayImage {
+        ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+            Luma([self.get(x, y, image.get_pixel(x, y).0[0])])
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tone {
+    pub equalize: Equalize,
+    pub brightness: f32,
+    pub contrast: f32,
+    pub gamma: f32,
+    pub black: u8,
+    pub white: u8,
+    pub invert: bool,
+    pub stretch: Option<f32>,
+}
+
+impl Default for Tone {
+    fn default() -> Self {
+        Tone {
+            equalize: Equalize::None,
+            brightness: 0.,
+            contrast: 1.,
+            gamma: 1.,
+            black: 0,
+            white: 255,
+            invert: false,
+            stretch: None,
+        }
+    }
+}
+
+impl Tone {
+    fn is_identity(&self) -> bool {
+        *self == Tone::default()
+    }
+
+    pub(crate) fn apply(&self, image: GrayImage) -> GrayImage {
+        if self.is_identity() {
+            return image;
+        }
+
+        let (mapping, table) = self.curve(&image);
+
+        ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+            Luma([table[mapping.get(x, y, image.get_pixel(x, y).0[0]) as usize]])
+        })
+    }
+
+    pub(crate) fn apply_colors<'a>(
+        &self,
+        luma: &GrayImage,
+        colors: impl Iterator<Item = &'a mut Rgb<u8>>,
+    ) {
+        if self.is_identity() {
+            return;
+        }
+
+        let (mapping, table) = self.curve(luma);
+        let width = luma.width() as usize;
+
+        for (i, color) in colors.enumerate() {
+            let (x, y) = ((i % width) as u32, (i / width) as u32);
+
+            for c in color.0.iter_mut() {
+                *c = table[mapping.get(x, y, *c) as usize];
+            }
+        }
+    }
+
+    fn curve(&self, image: &GrayImage) -> (Mapping, [u8; 256]) {
+        let mapping = self.equalize.mapping(image);
+
+        let (low, high) = match self.stretch {
+            Some(clip) => percentiles(&mapping.apply(image), clip),
+            None => (0, 255),
+        };
+        let black = self.black as f32 / 255.;
+        let white = self.white as f32 / 255.;
+
+        let table = std::array::from_fn(|luma| {
+            let mut v = (luma as f32 - low as f32) / (high as f32 - low as f32).max(1.);
+
+            v = ((v - black) / (white - black).max(1. / 255.)).clamp(0., 1.);
+            v = v.powf(1. / self.gamma);
+            v = (v - 0.5) * self.contrast + 0.5 + self.brightness;
+            v = v.clamp(0., 1.);
+
+            if self.invert {
+                v = 1. - v;
+            }
+
+            (v * 255.).round() as u8
+        });
+
+        (mapping, table)
+    }
+}
+
+fn histogram<'a>(pixels: impl Iterator<Item = &'a Luma<u8>>) -> [usize; 256] {
+    let mut histogram = [0usize; 256];
+
+    for pixel in pixels {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+
+    histogram
+}
+
+fn cdf_table(histogram: &[f32; 256]) -> [u8; 256] {
+    let total: f32 = histogram.iter().sum();
+    let lowest = histogram
+        .iter()
+        .copied()
+        .find(|count| *count > 0.)
+        .unwrap_or(0.);
+
+    if total <= lowest {
+        return std::array::from_fn(|value| value as u8);
+    }
+
+    let mut table = [0; 256];
+    let mut cdf = 0.;
+
+    for (value, count) in table.iter_mut().zip(histogram) {
+        cdf += count;
+        *value = ((cdf - lowest) / (total - lowest) * 255.).round() as u8;
+    }
+
+    table
+}
+
+fn equalize(image: &GrayImage) -> Mapping {
+    Mapping::Global(Box::new(cdf_table(
+        &histogram(image.pixels()).map(|count| count as f32),
+    )))
+}
+
+fn clahe(image: &GrayImage, tile: u32, clip: f32) -> Mapping {
+    let (width, height) = image.dimensions();
+    let tiles_x = width.div_ceil(tile).max(1);
+    let tiles_y = height.div_ceil(tile).max(1);
+
+    let tables: Vec<[u8; 256]> = (0..tiles_y)
+        .flat_map(|ty| (0..tiles_x).map(move |tx| (tx, ty)))
+        .map(|(tx, ty)| {
+            let (x0, y0) = (tx * tile, ty * tile);
+            let (x1, y1) = ((x0 + tile).min(width), (y0 + tile).min(height));
+            let pixels = (y0..y1).flat_map(|y| (x0..x1).map(move |x| image.get_pixel(x, y)));
+
+            let mut histogram = histogram(pixels).map(|count| count as f32);
+            let limit = (clip * ((x1 - x0) * (y1 - y0)) as f32 / 256.).max(1.);
+            let mut excess = 0.;
+
+            for count in histogram.iter_mut() {
+                excess += (*count - limit).max(0.);
+                *count = count.min(limit);
+            }
+
+            for count in histogram.iter_mut() {
+                *count += excess / 256.;
+            }
+
+            cdf_table(&histogram)
+        })
+        .collect();
+
+    Mapping::Tiles {
+        tile,
+        columns: tiles_x,
+        rows: tiles_y,
+        tables,
+    }
+}
+
+fn percentiles(image: &GrayImage, clip: f32) -> (u8, u8) {
+    let histogram = histogram(image.pixels());
+
+    let total = image.pixels().len();
+    let skip = (total as f32 * clip / 100.) as usize;
+
+    let low = clipped_level(&histogram, 0..256, skip);
+    let high = clipped_level(&histogram, (0..256).rev(), skip);
+
+    if low < high {
+        (low, high)
+    } else {
+        (0, 255)
+    }
+}
+
+fn clipped_level(
+    histogram: &[usize; 256],
+    mut levels: impl Iterator<Item = usize>,
+    skip: usize,
+) -> u8 {
+    let mut count = 0;
+
+    levels
+        .find(|level| {
+            count += histogram[*level];
+            count > skip
+        })
+        .unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_levels() -> GrayImage {
+        ImageBuffer::from_fn(8, 8, |x, _| Luma([if x < 4 { 50 } else { 200 }]))
+    }
+
+    #[test]
+    fn equalize_spreads_levels() {
+        let image = equalize(&two_levels()).apply(&two_levels());
+
+        assert_eq!(image.get_pixel(0, 0).0[0], 0);
+        assert_eq!(image.get_pixel(7, 0).0[0], 255);
+    }
+
+    #[test]
+    fn equalize_keeps_flat_images() {
+        let image = GrayImage::from_pixel(8, 8, Luma([128]));
+
+        assert_eq!(equalize(&image).apply(&image), image);
+    }
+
+    #[test]
+    fn cdf_table_is_monotonic() {
+        let mut histogram = [0.; 256];
+
+        histogram[10] = 3.;
+        histogram[100] = 1.;
+        histogram[240] = 4.;
+
+        let table = cdf_table(&histogram);
+
+        assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!((table[10], table[100], table[240]), (0, 51, 255));
+    }
+
+    #[test]
+    fn clahe_single_unclipped_tile_matches_global() {
+        let image = two_levels();
+
+        assert_eq!(
+            clahe(&image, 8, 1000.).apply(&image),
+            equalize(&image).apply(&image)
+        );
+    }
+
+    #[test]
+    fn clahe_identical_tiles_match_global() {
+        let image = ImageBuffer::from_fn(16, 8, |x, y| Luma([((x % 4) * 50 + (y % 4) * 10) as u8]));
+
+        assert_eq!(
+            clahe(&image, 4, 1000.).apply(&image),
+            equalize(&image).apply(&image)
+        );
+    }
+
+    #[test]
+    fn colors_follow_the_curve() {
+        let luma = two_levels();
+        let mut colors = [Rgb([10, 128, 250]); 64];
+        let tone = Tone {
+            invert: true,
+            ..Tone::default()
+        };
+
+        tone.apply_colors(&luma, colors.iter_mut());
+        assert_eq!(colors[0], Rgb([245, 127, 5]));
+
+        let tone = Tone {
+            brightness: 1.,
+            ..Tone::default()
+        };
+
+        tone.apply_colors(&luma, colors.iter_mut());
+        assert_eq!(colors[63], Rgb([255; 3]));
+    }
+}